@@ -0,0 +1,59 @@
+use std::fs;
+
+use ufmclient::{PartitionSet, Plan, PlanOptions, UFMConfig, UFMError};
+
+pub fn load_partitions(file: &str) -> Result<PartitionSet, UFMError> {
+    let data = fs::read_to_string(file)
+        .map_err(|e| UFMError::InvalidConfig(format!("failed to read {}: {}", file, e)))?;
+
+    // YAML is a superset of JSON, so both of them are supported.
+    serde_yaml::from_str(&data)
+        .map_err(|e| UFMError::InvalidConfig(format!("failed to parse {}: {}", file, e)))
+}
+
+pub async fn plan(conf: UFMConfig, file: &str, prune: bool) -> Result<(), UFMError> {
+    let ufm = ufmclient::connect(conf)?;
+    let desired = load_partitions(file)?;
+    let opts = PlanOptions {
+        prune,
+        ..PlanOptions::default()
+    };
+    let plan = ufm.plan(&desired.partitions, opts).await?;
+    print_plan(&plan);
+
+    Ok(())
+}
+
+pub async fn run(conf: UFMConfig, file: &str, prune: bool) -> Result<(), UFMError> {
+    let ufm = ufmclient::connect(conf)?;
+    let desired = load_partitions(file)?;
+    let opts = PlanOptions {
+        prune,
+        ..PlanOptions::default()
+    };
+    let plan = ufm.plan(&desired.partitions, opts).await?;
+    print_plan(&plan);
+    if plan.is_empty() {
+        return Ok(());
+    }
+
+    ufm.apply(&plan).await?;
+    crate::print_dry_run(&ufm);
+
+    match ufm.is_dry_run() {
+        true => println!("Dry run, no change was applied."),
+        false => println!("Applied {} change(s).", plan.actions.len()),
+    }
+
+    Ok(())
+}
+
+/// Print the actions of the plan, or "No changes." if there is none.
+pub fn print_plan(plan: &Plan) {
+    if plan.is_empty() {
+        println!("No changes.");
+    }
+    for action in &plan.actions {
+        println!("{}", action);
+    }
+}
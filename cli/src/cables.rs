@@ -0,0 +1,99 @@
+use ufmclient::{Cable, UFMConfig, UFMError};
+
+/// The columns of `ufm cables`, with their width in the table.
+const COLUMNS: [(&str, usize); 15] = [
+    ("port", 24),
+    ("port_guid", 20),
+    ("peer_port", 24),
+    ("peer_guid", 20),
+    ("vendor", 12),
+    ("part_number", 20),
+    ("serial_number", 16),
+    ("length", 8),
+    ("technology", 32),
+    ("revision", 10),
+    ("firmware", 12),
+    ("temperature", 12),
+    ("tx_power", 24),
+    ("rx_power", 24),
+    ("identifier", 12),
+];
+
+fn opt(v: &Option<String>) -> String {
+    v.clone().unwrap_or_default()
+}
+
+fn column(cable: &Cable, name: &str) -> String {
+    match name {
+        "port" => cable.port.clone(),
+        "port_guid" => cable.port_guid.clone(),
+        "peer_port" => cable.peer_port.clone(),
+        "peer_guid" => cable.peer_guid.clone(),
+        "vendor" => opt(&cable.vendor),
+        "part_number" => opt(&cable.part_number),
+        "serial_number" => opt(&cable.serial_number),
+        "length" => opt(&cable.length),
+        "technology" => opt(&cable.technology),
+        "revision" => opt(&cable.revision),
+        "firmware" => opt(&cable.firmware_version),
+        "temperature" => opt(&cable.temperature),
+        "tx_power" => opt(&cable.tx_power),
+        "rx_power" => opt(&cable.rx_power),
+        "identifier" => opt(&cable.identifier),
+        _ => String::new(),
+    }
+}
+
+/// Quote the CSV field if it has a separator, quote or line break.
+fn csv_field(v: &str) -> String {
+    if v.contains([',', '"', '\n']) {
+        format!("\"{}\"", v.replace('"', "\"\""))
+    } else {
+        v.to_string()
+    }
+}
+
+pub async fn run(conf: UFMConfig, port: &Option<String>, format: &str) -> Result<(), UFMError> {
+    let ufm = ufmclient::connect(conf)?;
+    let cables = match port {
+        Some(p) => vec![ufm.get_cable(p).await?],
+        None => ufm.list_cables().await?,
+    };
+
+    match format.to_lowercase().as_str() {
+        "table" => {
+            let header: Vec<String> = COLUMNS
+                .iter()
+                .take(9)
+                .map(|(c, w)| format!("{:<w$}", c.to_uppercase(), w = w))
+                .collect();
+            println!("{}", header.join(""));
+            for cable in &cables {
+                let row: Vec<String> = COLUMNS
+                    .iter()
+                    .take(9)
+                    .map(|(c, w)| format!("{:<w$}", column(cable, c), w = w))
+                    .collect();
+                println!("{}", row.join(""));
+            }
+        }
+        "csv" => {
+            println!("{}", COLUMNS.map(|(c, _)| c).join(","));
+            for cable in &cables {
+                let row: Vec<String> = COLUMNS
+                    .iter()
+                    .map(|(c, _)| csv_field(&column(cable, c)))
+                    .collect();
+                println!("{}", row.join(","));
+            }
+        }
+        "json" => println!(
+            "{}",
+            serde_json::to_string_pretty(&cables)
+                .map_err(|e| UFMError::InvalidConfig(e.to_string()))?
+        ),
+        f => return Err(UFMError::InvalidConfig(format!("unknown format {}", f))),
+    };
+
+    Ok(())
+}
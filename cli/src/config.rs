@@ -0,0 +1,52 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use serde::{Deserialize, Serialize};
+use ufmclient::{PartitionKey, QoSProfiles, UFMError};
+
+/// The config file of the command line, e.g.
+///
+/// ```yaml
+/// profiles:
+///   storage:
+///     mtu_limit: 4
+///     service_level: 1
+///     rate_limit: 100
+/// assignments:
+///   "0x100": storage
+/// ```
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct CliConfig {
+    /// The QoS profiles, which override the builtin ones with the same name.
+    #[serde(default = "QoSProfiles::empty")]
+    pub profiles: QoSProfiles,
+    /// The QoS profile assigned to each partition.
+    #[serde(default)]
+    pub assignments: BTreeMap<PartitionKey, String>,
+}
+
+impl CliConfig {
+    pub fn load(file: &Option<String>) -> Result<CliConfig, UFMError> {
+        let file = match file {
+            Some(f) => f,
+            None => return Ok(CliConfig::default()),
+        };
+
+        let data = fs::read_to_string(file)
+            .map_err(|e| UFMError::InvalidConfig(format!("failed to read {}: {}", file, e)))?;
+
+        let config: CliConfig = serde_yaml::from_str(&data)
+            .map_err(|e| UFMError::InvalidConfig(format!("failed to parse {}: {}", file, e)))?;
+        config.profiles.validate()?;
+
+        Ok(config)
+    }
+
+    /// The builtin profiles with the ones in the config file.
+    pub fn profiles(&self) -> QoSProfiles {
+        let mut profiles = QoSProfiles::default();
+        profiles.merge(self.profiles.clone());
+
+        profiles
+    }
+}
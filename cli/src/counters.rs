@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use ufmclient::{PortCounters, UFMConfig, UFMError};
+
+pub async fn run(
+    conf: UFMConfig,
+    ports: &[String],
+    watch: bool,
+    interval: u64,
+) -> Result<(), UFMError> {
+    let ufm = ufmclient::connect(conf)?;
+
+    let mut last: HashMap<String, PortCounters> = HashMap::new();
+    loop {
+        for c in ufm.port_counters(ports).await? {
+            print_counters(&c, last.get(&c.port));
+            last.insert(c.port.clone(), c);
+        }
+
+        if !watch {
+            break;
+        }
+        tokio::time::sleep(Duration::from_secs(interval)).await;
+    }
+
+    Ok(())
+}
+
+pub async fn reset(conf: UFMConfig, ports: &[String]) -> Result<(), UFMError> {
+    let ufm = ufmclient::connect(conf)?;
+    let jobs = ufm.reset_port_counters(ports).await?;
+
+    println!("{:<30}{:<20}", "Port", "Job");
+    for job in jobs {
+        println!("{:<30}{:<20}", job.port, job.job_id.unwrap_or_default());
+    }
+    crate::print_dry_run(&ufm);
+
+    Ok(())
+}
+
+/// Print the counters of the port, with the changes since the last sample in watch mode.
+fn print_counters(c: &PortCounters, last: Option<&PortCounters>) {
+    let counters = |c: &PortCounters| {
+        [
+            ("XmitData", c.xmit_data),
+            ("RcvData", c.rcv_data),
+            ("XmitPackets", c.xmit_packets),
+            ("RcvPackets", c.rcv_packets),
+            ("SymbolErrors", c.symbol_errors),
+            ("LinkDowned", c.link_downed),
+            ("LinkErrorRecovery", c.link_error_recovery),
+            ("RcvErrors", c.rcv_errors),
+            ("RcvRemotePhysErrors", c.rcv_remote_physical_errors),
+            ("RcvSwitchRelayErrors", c.rcv_switch_relay_errors),
+            ("XmitDiscards", c.xmit_discards),
+            ("XmitConstraintErrors", c.xmit_constraint_errors),
+            ("RcvConstraintErrors", c.rcv_constraint_errors),
+            ("LocalLinkIntegrity", c.local_link_integrity_errors),
+            ("BufferOverrunErrors", c.excessive_buffer_overrun_errors),
+            ("VL15Dropped", c.vl15_dropped),
+            ("XmitWait", c.xmit_wait),
+        ]
+    };
+
+    println!("{:25}: {}", "Port", c.port);
+    println!("{:25}: {}", "GUID", c.guid);
+    let previous = last.map(counters);
+    for (i, (name, v)) in counters(c).iter().enumerate() {
+        match &previous {
+            Some(p) if p[i].1 != *v => {
+                println!("{:25}: {} (+{})", name, v, v.saturating_sub(p[i].1))
+            }
+            _ => println!("{:25}: {}", name, v),
+        }
+    }
+    println!();
+}
@@ -1,16 +1,20 @@
+use std::str::FromStr;
+
 use ufmclient::{
-    Partition, PartitionKey, PartitionQoS, PortConfig, PortMembership, UFMConfig, UFMError,
+    Partition, PartitionKey, PartitionKeyRange, PartitionQoS, PortConfig, PortMembership,
+    UFMConfig, UFMError, DEFAULT_PKEY,
 };
 
 pub struct CreateOptions {
     pub pkey: String,
-    pub mtu: u16,
+    pub name: String,
+    pub qos: PartitionQoS,
     pub ipoib: bool,
     pub index0: bool,
     pub membership: String,
-    pub service_level: u8,
-    pub rate_limit: f64,
     pub guids: Vec<String>,
+    pub hosts: Vec<String>,
+    pub range: Option<String>,
 }
 
 pub async fn run(conf: UFMConfig, opt: &CreateOptions) -> Result<(), UFMError> {
@@ -25,18 +29,45 @@ pub async fn run(conf: UFMConfig, opt: &CreateOptions) -> Result<(), UFMError> {
         })
     }
 
-    let p = Partition {
-        name: "".to_string(),
-        pkey: PartitionKey::try_from(opt.pkey.clone())?,
-        ipoib: opt.ipoib,
-        qos: PartitionQoS {
-            mtu_limit: opt.mtu,
-            service_level: opt.service_level,
-            rate_limit: opt.rate_limit,
+    let p = match opt.pkey.as_str() {
+        "auto" => {
+            let range = opt
+                .range
+                .as_ref()
+                .ok_or(UFMError::InvalidConfig("--range is required".to_string()))?;
+            let template = Partition {
+                name: opt.name.clone(),
+                pkey: DEFAULT_PKEY,
+                ipoib: opt.ipoib,
+                qos: opt.qos.clone(),
+            };
+            let p = ufm
+                .allocate_partition(PartitionKeyRange::from_str(range)?, &template)
+                .await?;
+            println!("{}", p.pkey);
+            p
+        }
+        _ => Partition {
+            name: opt.name.clone(),
+            pkey: PartitionKey::try_from(opt.pkey.clone())?,
+            ipoib: opt.ipoib,
+            qos: opt.qos.clone(),
         },
     };
 
-    ufm.bind_ports(p, pbs).await?;
+    if !opt.hosts.is_empty() {
+        let membership = PortMembership::try_from(opt.membership.clone())?;
+        let changes = ufm
+            .bind_hosts(p.clone(), opt.hosts.clone(), membership, opt.index0)
+            .await?;
+        crate::ports::print_changes(&changes);
+    }
+
+    if !pbs.is_empty() || opt.hosts.is_empty() {
+        let changes = ufm.bind_ports(p, pbs).await?;
+        crate::ports::print_changes(&changes);
+    }
+    crate::print_dry_run(&ufm);
 
     Ok(())
 }
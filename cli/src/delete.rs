@@ -1,8 +1,9 @@
 use ufmclient::{UFMConfig, UFMError};
 
-pub async fn run(conf: UFMConfig, pkey: &String) -> Result<(), UFMError> {
+pub async fn run(conf: UFMConfig, pkey: &str, force: bool) -> Result<(), UFMError> {
     let ufm = ufmclient::connect(conf)?;
-    ufm.delete_partition(pkey).await?;
+    ufm.delete_partition(pkey, force).await?;
+    crate::print_dry_run(&ufm);
 
     Ok(())
 }
@@ -0,0 +1,70 @@
+use std::fs;
+
+use ufmclient::{DrainRecord, UFMConfig, UFMError};
+
+pub async fn drain(
+    conf: UFMConfig,
+    guids: &[String],
+    hosts: &[String],
+    output: &str,
+) -> Result<(), UFMError> {
+    let ufm = ufmclient::connect(conf)?;
+
+    let mut guids = guids.to_vec();
+    if !hosts.is_empty() {
+        guids.extend(ufm.resolve_hosts(hosts).await?.into_values().flatten());
+    }
+    if guids.is_empty() {
+        return Err(UFMError::InvalidConfig("no GUIDs".to_string()));
+    }
+
+    let record = ufm.drain_record(guids).await?;
+
+    let data = serde_json::to_string_pretty(&record)
+        .map_err(|e| UFMError::InvalidConfig(e.to_string()))?;
+    // Save the record before draining, so the GUIDs can always be restored; nothing
+    // is drained in dry-run mode, so print the record instead of writing it.
+    match ufm.is_dry_run() {
+        true => println!("{}", data),
+        false => write_atomically(output, &data)?,
+    };
+
+    ufm.drain(&record).await?;
+
+    for m in &record.memberships {
+        println!("{:<20}drained from {} ({})", m.guid, m.pkey, m.membership);
+    }
+    crate::print_dry_run(&ufm);
+
+    Ok(())
+}
+
+pub async fn undrain(conf: UFMConfig, file: &str) -> Result<(), UFMError> {
+    let ufm = ufmclient::connect(conf)?;
+
+    let data = fs::read_to_string(file)
+        .map_err(|e| UFMError::InvalidConfig(format!("failed to read {}: {}", file, e)))?;
+    let record: DrainRecord = serde_json::from_str(&data)
+        .map_err(|e| UFMError::InvalidConfig(format!("failed to parse {}: {}", file, e)))?;
+
+    ufm.restore(&record).await?;
+
+    for m in &record.memberships {
+        println!("{:<20}restored to {} ({})", m.guid, m.pkey, m.membership);
+    }
+    crate::print_dry_run(&ufm);
+
+    Ok(())
+}
+
+/// Write the file through a temporary file in the same directory, so the file is
+/// either the old one or the complete new one.
+fn write_atomically(file: &str, data: &str) -> Result<(), UFMError> {
+    let tmp = format!("{}.tmp", file);
+    fs::write(&tmp, data)
+        .and_then(|_| fs::rename(&tmp, file))
+        .map_err(|e| {
+            let _ = fs::remove_file(&tmp);
+            UFMError::InvalidConfig(format!("failed to write {}: {}", file, e))
+        })
+}
@@ -1,11 +1,32 @@
+use std::io::{self, Write};
+
 use clap::{Parser, Subcommand};
-use ufmclient::{UFMConfig, UFMError};
+use ufmclient::{
+    LogicalState, Mtu, PartitionQoS, PortAction, PortFilter, RateLimit, ServiceLevel, SystemFilter,
+    SystemType, UFMConfig, UFMError, Ufm,
+};
 
+mod apply;
+mod cables;
+mod config;
+mod counters;
 mod create;
 mod delete;
+mod drain;
 mod list;
+mod orphan;
+mod port;
+mod port_list;
+mod ports;
+mod qos_check;
+mod reachability;
+mod remove;
+mod snapshot;
+mod systems;
+mod topology;
 mod version;
 mod view;
+mod whois;
 
 #[derive(Parser)]
 #[command(name = "ufm")]
@@ -21,6 +42,12 @@ struct Options {
     ufm_password: Option<String>,
     #[clap(long, env = "UFM_TOKEN")]
     ufm_token: Option<String>,
+    /// The config file of QoS profiles and their assignments
+    #[clap(long, env = "UFM_CONFIG", global = true)]
+    config: Option<String>,
+    /// Print the requests which would change UFM instead of sending them
+    #[clap(long, global = true, default_value_t = false)]
+    dry_run: bool,
     #[clap(subcommand)]
     command: Option<Commands>,
 }
@@ -42,15 +69,25 @@ enum Commands {
         /// The pkey of the partition to delete
         #[arg(short, long)]
         pkey: String,
+        /// Force to delete the protected partition, e.g. the default pkey 0x7fff
+        #[arg(long, default_value_t = false)]
+        force: bool,
     },
     /// Create a partition
     Create {
-        /// The pkey for the new partition
+        /// The pkey for the new partition; "auto" to allocate a free pkey in the range
         #[arg(short, long)]
         pkey: String,
-        /// The MTU of the new partition
-        #[arg(long, default_value_t = 2048)]
-        mtu: u16,
+        /// The range to allocate the pkey from, e.g. "0x100-0x7ff"
+        #[arg(long)]
+        range: Option<String>,
+        /// The name of the new partition; required with "auto" to detect the pkeys
+        /// allocated concurrently, so it should be unique
+        #[arg(long, default_value_t = String::new())]
+        name: String,
+        /// The MTU of the new partition: 2k (2, 2048) or 4k (4, 4096)
+        #[arg(long, default_value_t = Mtu::Mtu2K)]
+        mtu: Mtu,
         /// The IPOverIB of the new partition
         #[arg(long, default_value_t = true)]
         ipoib: bool,
@@ -60,15 +97,288 @@ enum Commands {
         /// The Membership of the new partition
         #[arg(short, long, default_value_t = String::from("full"))]
         membership: String,
-        /// The ServiceLevel of the new partition
-        #[arg(short, long, default_value_t = 0)]
-        service_level: u8,
-        /// The RateLimit of the new partition
-        #[arg(short, long, default_value_t = 100.0)]
-        rate_limit: f64,
+        /// The ServiceLevel of the new partition, 0-15
+        #[arg(short, long, default_value_t = ServiceLevel::default())]
+        service_level: ServiceLevel,
+        /// The QoS profile of the new partition, e.g. storage or tenant-default
+        #[arg(long, conflicts_with_all = ["mtu", "service_level", "rate_limit"])]
+        profile: Option<String>,
+        /// The RateLimit of the new partition: 2.5, 5, 10, 14, 20, 25, 30, 40, 56, 60, 80, 100, 112, 120, 168, 200 or 300
+        #[arg(short, long, default_value_t = RateLimit::default())]
+        rate_limit: RateLimit,
         /// The GUIDs of the new partition
         #[arg(short, long)]
         guids: Vec<String>,
+        /// The hosts of the new partition, all ports of the hosts are added
+        #[arg(long, value_delimiter = ',')]
+        hosts: Vec<String>,
+    },
+    /// Remove the hosts from the partition
+    Remove {
+        /// The pkey of the partition
+        #[arg(short, long)]
+        pkey: String,
+        /// The hosts to remove, all ports of the hosts are removed
+        #[arg(long, value_delimiter = ',', required = true)]
+        hosts: Vec<String>,
+        /// Force to remove the hosts from the protected partition, e.g. the default pkey 0x7fff
+        #[arg(long, default_value_t = false)]
+        force: bool,
+    },
+    /// List the physical ports
+    Ports {
+        /// The type of the systems, e.g. Computer or Switch
+        #[arg(long)]
+        sys_type: Option<String>,
+        /// The GUID of the system
+        #[arg(long)]
+        system: Option<String>,
+        /// The logical state of the ports, e.g. Active or Down
+        #[arg(long)]
+        state: Option<String>,
+        /// The LID of the port
+        #[arg(long)]
+        lid: Option<i32>,
+        /// The GUIDs of the ports
+        #[arg(short, long, value_delimiter = ',')]
+        guids: Vec<String>,
+        /// The columns to print
+        #[arg(long, value_delimiter = ',', default_value = port_list::DEFAULT_COLUMNS)]
+        columns: Vec<String>,
+    },
+    /// Manage the ports
+    Port {
+        #[command(subcommand)]
+        command: PortCommands,
+    },
+    /// Add the GUIDs to an existing partition
+    AddPorts {
+        /// The pkey of the partition
+        #[arg(short, long)]
+        pkey: String,
+        /// The default membership of the GUIDs, overridden by "GUID:membership"
+        #[arg(short, long, default_value_t = String::from("full"))]
+        membership: String,
+        /// The Index0 of the GUIDs
+        #[arg(long, default_value_t = true)]
+        index0: bool,
+        /// The GUIDs, optionally with membership, e.g. "1070fd0300176625:limited"
+        #[arg(short, long, value_delimiter = ',')]
+        guids: Vec<String>,
+        /// Read the GUIDs from the file, one per line; "-" for stdin
+        #[arg(long)]
+        guids_from: Option<String>,
+    },
+    /// Remove the GUIDs from the partition
+    RemovePorts {
+        /// The pkey of the partition
+        #[arg(short, long)]
+        pkey: String,
+        /// The GUIDs to remove
+        #[arg(short, long, value_delimiter = ',')]
+        guids: Vec<String>,
+        /// Read the GUIDs from the file, one per line; "-" for stdin
+        #[arg(long)]
+        guids_from: Option<String>,
+        /// Force to remove the GUIDs from the protected partition, e.g. the default pkey 0x7fff
+        #[arg(long, default_value_t = false)]
+        force: bool,
+    },
+    /// Show the changes to reconcile UFM to the partitions in the file
+    Plan {
+        /// The YAML/JSON file of the desired partitions
+        #[arg(short, long)]
+        file: String,
+        /// Delete the partitions which are not in the file
+        #[arg(long, default_value_t = false)]
+        prune: bool,
+    },
+    /// Reconcile UFM to the partitions in the file
+    Apply {
+        /// The YAML/JSON file of the desired partitions
+        #[arg(short, long)]
+        file: String,
+        /// Delete the partitions which are not in the file
+        #[arg(long, default_value_t = false)]
+        prune: bool,
+    },
+    /// Export all partitions to a snapshot
+    Export {
+        /// The file to write the snapshot to; print to stdout if not set
+        #[arg(short, long)]
+        output: Option<String>,
+        /// The format of the snapshot: yaml or json
+        #[arg(long, default_value_t = String::from("yaml"))]
+        format: String,
+    },
+    /// Import partitions from a snapshot
+    Import {
+        /// The snapshot file in YAML/JSON
+        #[arg(short, long)]
+        file: String,
+        /// Remove the partitions and members which are not in the snapshot
+        #[arg(long, default_value_t = false)]
+        replace: bool,
+    },
+    /// Remove GUIDs or hosts from all their partitions, and save the memberships to a file
+    Drain {
+        /// The GUIDs to drain
+        #[arg(short, long, value_delimiter = ',')]
+        guids: Vec<String>,
+        /// The hosts to drain, all ports of the hosts are drained
+        #[arg(long, value_delimiter = ',')]
+        hosts: Vec<String>,
+        /// The file to save the drain record to
+        #[arg(short, long)]
+        output: String,
+    },
+    /// Restore the partition memberships from a drain record
+    Undrain {
+        /// The drain record file
+        #[arg(short, long)]
+        file: String,
+    },
+    /// Remove the partition members which do not exist in the fabric
+    PruneOrphans {
+        /// Do not ask for confirmation
+        #[arg(short, long, default_value_t = false)]
+        yes: bool,
+    },
+    /// Check whether GUIDs or hosts can communicate through partitions
+    Reachability {
+        /// The GUID or hostname to check from
+        #[arg(long, requires = "to")]
+        from: Option<String>,
+        /// The GUID or hostname to check to
+        #[arg(long, requires = "from")]
+        to: Option<String>,
+        /// The hosts to build the reachability matrix of
+        #[arg(long, value_delimiter = ',', conflicts_with_all = ["from", "to"])]
+        hosts: Vec<String>,
+        /// The format of the reachability matrix: csv or json
+        #[arg(long, default_value_t = String::from("csv"))]
+        format: String,
+    },
+    /// List the partitions whose QoS deviates from their assigned profiles in the config file
+    QosCheck,
+    /// List the cables and transceivers of the fabric
+    Cables {
+        /// Only show the cable of the port, which is a GUID or name
+        #[arg(short, long)]
+        port: Option<String>,
+        /// The output format: table, csv or json
+        #[arg(short, long, default_value_t = String::from("table"))]
+        format: String,
+    },
+    /// List the systems of the fabric, e.g. switches and hosts
+    Systems {
+        /// Only show the system of the GUID or name
+        #[arg(long, conflicts_with_all = ["sys_type", "model", "ip", "rack"])]
+        id: Option<String>,
+        /// The type of the systems: switch, host, gateway or router
+        #[arg(short = 't', long = "type")]
+        sys_type: Option<String>,
+        /// The model of the systems
+        #[arg(short, long)]
+        model: Option<String>,
+        /// The IP of the system
+        #[arg(long)]
+        ip: Option<String>,
+        /// The rack of the systems
+        #[arg(long)]
+        rack: Option<String>,
+    },
+    /// Manage the topology of the fabric
+    Topology {
+        #[command(subcommand)]
+        command: TopologyCommands,
+    },
+    /// List the partitions which a GUID or host is member of
+    Whois {
+        /// The port GUID or the hostname
+        target: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum TopologyCommands {
+    /// Export the topology as a graph of the systems and cables
+    Export {
+        /// The output format: dot, graphml or json
+        #[arg(short, long, default_value_t = String::from("dot"))]
+        format: String,
+        /// Only export the systems in the rack
+        #[arg(long)]
+        rack: Option<String>,
+        /// Only export the switches in the tiers, and the hosts linked to them
+        #[arg(long, value_delimiter = ',')]
+        tier: Vec<i32>,
+        /// Only export the members of the partition, and the switches linked to them
+        #[arg(short, long)]
+        pkey: Option<String>,
+    },
+    /// Validate the topology against a fat-tree design, e.g. after recabling
+    Validate {
+        /// The YAML file of the design, with tiers, radix and uplinks_per_leaf
+        #[arg(short, long)]
+        design: String,
+        /// The output format: text, json or yaml
+        #[arg(short, long, default_value_t = String::from("text"))]
+        format: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum PortCommands {
+    /// Disable the ports
+    Disable {
+        /// The GUIDs or names of the ports
+        #[arg(short, long, value_delimiter = ',', required = true)]
+        ports: Vec<String>,
+        /// Do not ask for confirmation of switch-to-switch links
+        #[arg(short, long, default_value_t = false)]
+        yes: bool,
+    },
+    /// Enable the ports
+    Enable {
+        /// The GUIDs or names of the ports
+        #[arg(short, long, value_delimiter = ',', required = true)]
+        ports: Vec<String>,
+        /// Do not ask for confirmation of switch-to-switch links
+        #[arg(short, long, default_value_t = false)]
+        yes: bool,
+    },
+    /// Reset the ports
+    Reset {
+        /// The GUIDs or names of the ports
+        #[arg(short, long, value_delimiter = ',', required = true)]
+        ports: Vec<String>,
+        /// Do not ask for confirmation of switch-to-switch links
+        #[arg(short, long, default_value_t = false)]
+        yes: bool,
+    },
+    /// View the performance and error counters of the ports
+    Counters {
+        /// The GUIDs or names of the ports
+        #[arg(short, long, value_delimiter = ',', required = true)]
+        guid: Vec<String>,
+        /// Keep reading the counters, and show the changes
+        #[arg(short, long, default_value_t = false)]
+        watch: bool,
+        /// The interval in seconds of watch mode
+        #[arg(short, long, default_value_t = 5, value_parser = clap::value_parser!(u64).range(1..))]
+        interval: u64,
+    },
+    /// Clear the performance and error counters of the ports
+    ResetCounters {
+        /// The GUIDs or names of the ports
+        #[arg(short, long, value_delimiter = ',', required = true)]
+        ports: Vec<String>,
+    },
+    /// View the job of a port action
+    Job {
+        /// The ID of the job
+        id: String,
     },
 }
 
@@ -80,7 +390,7 @@ async fn main() -> Result<(), UFMError> {
 
     let conf = load_conf(&opt);
     match &opt.command {
-        Some(Commands::Delete { pkey }) => delete::run(conf, pkey).await?,
+        Some(Commands::Delete { pkey, force }) => delete::run(conf, pkey, *force).await?,
         Some(Commands::Version) => version::run(conf).await?,
         Some(Commands::List) => list::run(conf).await?,
         Some(Commands::View { pkey }) => view::run(conf, pkey).await?,
@@ -93,19 +403,162 @@ async fn main() -> Result<(), UFMError> {
             service_level,
             rate_limit,
             guids,
+            hosts,
+            range,
+            name,
+            profile,
         }) => {
+            let qos = match profile {
+                Some(p) => config::CliConfig::load(&opt.config)?
+                    .profiles()
+                    .get(p)?
+                    .clone(),
+                None => PartitionQoS::builder()
+                    .mtu_limit(*mtu)
+                    .service_level(*service_level)
+                    .rate_limit(*rate_limit)
+                    .build(),
+            };
             let opt = create::CreateOptions {
                 pkey: pkey.to_string(),
-                mtu: *mtu,
+                name: name.to_string(),
+                qos,
                 ipoib: *ipoib,
                 index0: *index0,
                 membership: membership.to_string(),
-                service_level: *service_level,
-                rate_limit: *rate_limit,
                 guids: guids.to_vec(),
+                hosts: hosts.to_vec(),
+                range: range.clone(),
             };
             create::run(conf, &opt).await?
         }
+        Some(Commands::Plan { file, prune }) => apply::plan(conf, file, *prune).await?,
+        Some(Commands::Apply { file, prune }) => apply::run(conf, file, *prune).await?,
+        Some(Commands::Export { output, format }) => snapshot::export(conf, output, format).await?,
+        Some(Commands::Import { file, replace }) => snapshot::import(conf, file, *replace).await?,
+        Some(Commands::Whois { target }) => whois::run(conf, target).await?,
+        Some(Commands::Remove { pkey, hosts, force }) => {
+            remove::run(conf, pkey, hosts, *force).await?
+        }
+        Some(Commands::AddPorts {
+            pkey,
+            membership,
+            index0,
+            guids,
+            guids_from,
+        }) => {
+            let opt = ports::PortsOptions {
+                pkey: pkey.to_string(),
+                membership: membership.to_string(),
+                index0: *index0,
+                guids: guids.to_vec(),
+                guids_from: guids_from.clone(),
+            };
+            ports::add(conf, &opt).await?
+        }
+        Some(Commands::RemovePorts {
+            pkey,
+            guids,
+            guids_from,
+            force,
+        }) => ports::remove(conf, pkey, guids, guids_from, *force).await?,
+        Some(Commands::Drain {
+            guids,
+            hosts,
+            output,
+        }) => drain::drain(conf, guids, hosts, output).await?,
+        Some(Commands::Undrain { file }) => drain::undrain(conf, file).await?,
+        Some(Commands::PruneOrphans { yes }) => orphan::prune(conf, *yes).await?,
+        Some(Commands::Reachability {
+            from,
+            to,
+            hosts,
+            format,
+        }) => {
+            let opt = reachability::ReachabilityOptions {
+                from: from.clone(),
+                to: to.clone(),
+                hosts: hosts.to_vec(),
+                format: format.to_string(),
+            };
+            reachability::run(conf, &opt).await?
+        }
+        Some(Commands::QosCheck) => {
+            qos_check::run(conf, &config::CliConfig::load(&opt.config)?).await?
+        }
+        Some(Commands::Ports {
+            sys_type,
+            system,
+            state,
+            lid,
+            guids,
+            columns,
+        }) => {
+            let filter = PortFilter {
+                sys_type: sys_type.clone(),
+                system: system.clone(),
+                state: state.as_deref().map(LogicalState::from),
+                lid: *lid,
+                guids: guids.to_vec(),
+            };
+            port_list::run(conf, &filter, columns).await?
+        }
+        Some(Commands::Cables { port, format }) => cables::run(conf, port, format).await?,
+        Some(Commands::Systems {
+            id,
+            sys_type,
+            model,
+            ip,
+            rack,
+        }) => match id {
+            Some(id) => systems::view(conf, id).await?,
+            None => {
+                let filter = SystemFilter {
+                    system_type: sys_type.as_deref().map(SystemType::from),
+                    model: model.clone(),
+                    ip: ip.clone(),
+                    rack: rack.clone(),
+                };
+                systems::list(conf, &filter).await?
+            }
+        },
+        Some(Commands::Topology { command }) => match command {
+            TopologyCommands::Export {
+                format,
+                rack,
+                tier,
+                pkey,
+            } => {
+                let opt = topology::ExportOptions {
+                    format: format.clone(),
+                    rack: rack.clone(),
+                    tiers: tier.clone(),
+                    pkey: pkey.clone(),
+                };
+                topology::export(conf, &opt).await?
+            }
+            TopologyCommands::Validate { design, format } => {
+                topology::validate(conf, design, format).await?
+            }
+        },
+        Some(Commands::Port { command }) => match command {
+            PortCommands::Disable { ports, yes } => {
+                port::action(conf, PortAction::Disable, ports, *yes).await?
+            }
+            PortCommands::Enable { ports, yes } => {
+                port::action(conf, PortAction::Enable, ports, *yes).await?
+            }
+            PortCommands::Reset { ports, yes } => {
+                port::action(conf, PortAction::Reset, ports, *yes).await?
+            }
+            PortCommands::Counters {
+                guid,
+                watch,
+                interval,
+            } => counters::run(conf, guid, *watch, *interval).await?,
+            PortCommands::ResetCounters { ports } => counters::reset(conf, ports).await?,
+            PortCommands::Job { id } => port::job(conf, id).await?,
+        },
         None => {}
     };
 
@@ -123,5 +576,30 @@ fn load_conf(opt: &Options) -> UFMConfig {
         username: opt.ufm_username.clone(),
         password: opt.ufm_password.clone(),
         token: opt.ufm_token.clone(),
+        dry_run: opt.dry_run,
+    }
+}
+
+/// Ask the user to confirm the action on the terminal.
+fn confirm(msg: &str) -> bool {
+    print!("{} [y/N] ", msg);
+    let _ = io::stdout().flush();
+
+    let mut answer = String::new();
+    if io::stdin().read_line(&mut answer).is_err() {
+        return false;
+    }
+
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+/// Print the requests recorded in dry-run mode.
+fn print_dry_run(ufm: &Ufm) {
+    if !ufm.is_dry_run() {
+        return;
+    }
+
+    for record in ufm.take_dry_run_records() {
+        println!("would send: {}", record);
     }
 }
@@ -0,0 +1,40 @@
+use std::collections::BTreeMap;
+
+use ufmclient::{UFMConfig, UFMError};
+
+pub async fn prune(conf: UFMConfig, yes: bool) -> Result<(), UFMError> {
+    let ufm = ufmclient::connect(conf)?;
+    let orphans = ufm.find_orphaned_members().await?;
+
+    if orphans.is_empty() {
+        println!("No orphaned members.");
+        return Ok(());
+    }
+
+    println!(
+        "{:<20}{:<10}{:<20}{:<12}",
+        "GUID", "Pkey", "Name", "Membership"
+    );
+    let mut pkeys = BTreeMap::new();
+    for m in orphans {
+        println!(
+            "{:<20}{:<10}{:<20}{:<12}",
+            m.guid, m.pkey, m.name, m.membership
+        );
+        pkeys.entry(m.pkey).or_insert_with(Vec::new).push(m.guid);
+    }
+
+    if !ufm.is_dry_run() && !yes && !crate::confirm("Remove the orphaned members?") {
+        return Ok(());
+    }
+
+    for (pkey, guids) in pkeys {
+        let changes = ufm.unbind_ports(pkey, guids, false).await?;
+        for p in changes.removed {
+            println!("{:<20}removed from {}", p.guid, pkey);
+        }
+    }
+    crate::print_dry_run(&ufm);
+
+    Ok(())
+}
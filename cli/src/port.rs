@@ -0,0 +1,51 @@
+use ufmclient::{PortAction, UFMConfig, UFMError};
+
+pub async fn action(
+    conf: UFMConfig,
+    action: PortAction,
+    ports: &[String],
+    yes: bool,
+) -> Result<(), UFMError> {
+    let ufm = ufmclient::connect(conf)?;
+
+    let targets = ufm.resolve_ports(ports).await?;
+    let jobs = match ufm
+        .port_action(action, &targets, yes || ufm.is_dry_run())
+        .await
+    {
+        Err(UFMError::ProtectedPort(isl)) => {
+            println!("The ports are switch-to-switch links: {}", isl);
+            if !crate::confirm(&format!("Are you sure to {} them?", action)) {
+                return Ok(());
+            }
+            ufm.port_action(action, &targets, true).await?
+        }
+        jobs => jobs?,
+    };
+
+    println!("{:<30}{:<10}{:<20}", "Port", "Action", "Job");
+    for job in jobs {
+        println!(
+            "{:<30}{:<10}{:<20}",
+            job.port,
+            job.action,
+            job.job_id.unwrap_or_default()
+        );
+    }
+    crate::print_dry_run(&ufm);
+
+    Ok(())
+}
+
+pub async fn job(conf: UFMConfig, id: &str) -> Result<(), UFMError> {
+    let ufm = ufmclient::connect(conf)?;
+    let job = ufm.get_job(id).await?;
+
+    println!("{:15}: {}", "ID", job.id);
+    println!("{:15}: {}", "Status", job.status);
+    println!("{:15}: {}", "Progress", job.progress);
+    println!("{:15}: {}", "Description", job.description);
+    println!("{:15}: {}", "Summary", job.summary);
+
+    Ok(())
+}
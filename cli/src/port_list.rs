@@ -0,0 +1,85 @@
+use ufmclient::{PhysicalPort, PortFilter, UFMConfig, UFMError};
+
+/// The columns of `ufm ports`, with their width.
+const COLUMNS: [(&str, usize); 16] = [
+    ("guid", 20),
+    ("name", 24),
+    ("system", 20),
+    ("system_id", 20),
+    ("lid", 8),
+    ("number", 8),
+    ("logical_state", 14),
+    ("physical_state", 16),
+    ("speed", 8),
+    ("width", 8),
+    ("mtu", 8),
+    ("peer_guid", 20),
+    ("peer", 32),
+    ("dname", 32),
+    ("module", 10),
+    ("severity", 10),
+];
+
+pub const DEFAULT_COLUMNS: &str = "guid,name,system,lid,logical_state,physical_state,speed,width";
+
+fn opt<T: ToString>(v: &Option<T>) -> String {
+    v.as_ref().map(|v| v.to_string()).unwrap_or_default()
+}
+
+fn column(port: &PhysicalPort, name: &str) -> String {
+    match name {
+        "guid" => port.guid.clone(),
+        "name" => port.name.clone(),
+        "system" => port.system_name.clone(),
+        "system_id" => port.system_id.clone(),
+        "lid" => port.lid.to_string(),
+        "number" => opt(&port.number),
+        "logical_state" => port.logical_state.to_string(),
+        "physical_state" => opt(&port.physical_state),
+        "speed" => opt(&port.active_speed),
+        "width" => opt(&port.active_width),
+        "mtu" => opt(&port.mtu),
+        "peer_guid" => opt(&port.peer_guid),
+        "peer" => opt(&port.peer_port_dname),
+        "dname" => opt(&port.dname),
+        "module" => opt(&port.module),
+        "severity" => opt(&port.severity),
+        _ => String::new(),
+    }
+}
+
+pub async fn run(conf: UFMConfig, filter: &PortFilter, columns: &[String]) -> Result<(), UFMError> {
+    let mut cols = vec![];
+    for c in columns {
+        let c = c.trim().to_lowercase();
+        let width = COLUMNS
+            .iter()
+            .find(|(n, _)| *n == c)
+            .map(|(_, w)| *w)
+            .ok_or(UFMError::InvalidConfig(format!(
+                "unknown column {}, available columns: {}",
+                c,
+                COLUMNS.map(|(n, _)| n).join(",")
+            )))?;
+        cols.push((c, width));
+    }
+
+    let ufm = ufmclient::connect(conf)?;
+    let ports = ufm.list_physical_ports(filter).await?;
+
+    let header: Vec<String> = cols
+        .iter()
+        .map(|(c, w)| format!("{:<w$}", c.to_uppercase(), w = w))
+        .collect();
+    println!("{}", header.join(""));
+
+    for port in ports {
+        let row: Vec<String> = cols
+            .iter()
+            .map(|(c, w)| format!("{:<w$}", column(&port, c), w = w))
+            .collect();
+        println!("{}", row.join(""));
+    }
+
+    Ok(())
+}
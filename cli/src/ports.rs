@@ -0,0 +1,127 @@
+use std::fs;
+use std::io::{self, Read};
+
+use ufmclient::{ChangeSet, PartitionKey, PortConfig, PortMembership, UFMConfig, UFMError};
+
+pub struct PortsOptions {
+    pub pkey: String,
+    pub membership: String,
+    pub index0: bool,
+    pub guids: Vec<String>,
+    pub guids_from: Option<String>,
+}
+
+/// Collect the entries from the options and the file (or stdin for "-"); each entry is
+/// a GUID, optionally with its own membership, e.g. "1070fd0300176625:limited".
+fn load_entries(guids: &[String], guids_from: &Option<String>) -> Result<Vec<String>, UFMError> {
+    let mut entries = guids.to_vec();
+
+    if let Some(from) = guids_from {
+        let mut data = String::new();
+        match from.as_str() {
+            "-" => io::stdin()
+                .read_to_string(&mut data)
+                .map(|_| ())
+                .map_err(|e| UFMError::InvalidConfig(format!("failed to read stdin: {}", e)))?,
+            f => {
+                data = fs::read_to_string(f)
+                    .map_err(|e| UFMError::InvalidConfig(format!("failed to read {}: {}", f, e)))?
+            }
+        };
+
+        for line in data.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if !line.is_empty() {
+                entries.push(line.split_whitespace().collect::<Vec<_>>().join(":"));
+            }
+        }
+    }
+
+    if entries.is_empty() {
+        return Err(UFMError::InvalidConfig("no GUIDs".to_string()));
+    }
+
+    Ok(entries)
+}
+
+fn load_ports(opt: &PortsOptions) -> Result<Vec<PortConfig>, UFMError> {
+    let mut ports = vec![];
+    for entry in load_entries(&opt.guids, &opt.guids_from)? {
+        let (guid, membership) = match entry.split_once(':') {
+            Some((g, m)) => (g, m.to_string()),
+            None => (entry.as_str(), opt.membership.clone()),
+        };
+        ports.push(PortConfig {
+            guid: guid.trim().to_lowercase(),
+            index0: opt.index0,
+            membership: PortMembership::try_from(membership)?,
+        });
+    }
+
+    Ok(ports)
+}
+
+pub async fn add(conf: UFMConfig, opt: &PortsOptions) -> Result<(), UFMError> {
+    let ufm = ufmclient::connect(conf)?;
+    let ports = load_ports(opt)?;
+
+    let p = ufm.get_partition(&opt.pkey).await?;
+    let changes = ufm.bind_ports(p, ports).await?;
+
+    print_changes(&changes);
+    crate::print_dry_run(&ufm);
+
+    Ok(())
+}
+
+pub async fn remove(
+    conf: UFMConfig,
+    pkey: &str,
+    guids: &[String],
+    guids_from: &Option<String>,
+    force: bool,
+) -> Result<(), UFMError> {
+    let ufm = ufmclient::connect(conf)?;
+
+    // The membership of the entries is ignored, so the file of add-ports can be reused.
+    let guids = load_entries(guids, guids_from)?
+        .iter()
+        .map(|e| {
+            e.split(':')
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_lowercase()
+        })
+        .collect();
+    let pkey = PartitionKey::try_from(pkey)?;
+    let changes = ufm.unbind_ports(pkey, guids, force).await?;
+
+    print_changes(&changes);
+    crate::print_dry_run(&ufm);
+
+    Ok(())
+}
+
+pub fn print_changes(changes: &ChangeSet) {
+    for p in &changes.added {
+        println!("{:<20}added ({})", p.guid, p.membership);
+    }
+    for c in &changes.membership_changed {
+        if c.from != c.to {
+            println!("{:<20}membership changed ({} -> {})", c.guid, c.from, c.to);
+        }
+        if c.from_index0 != c.to_index0 {
+            println!(
+                "{:<20}index0 changed ({} -> {})",
+                c.guid, c.from_index0, c.to_index0
+            );
+        }
+    }
+    for p in &changes.removed {
+        println!("{:<20}removed ({})", p.guid, p.membership);
+    }
+    for g in &changes.unchanged {
+        println!("{:<20}unchanged", g);
+    }
+}
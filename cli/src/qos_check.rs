@@ -0,0 +1,38 @@
+use ufmclient::{UFMConfig, UFMError};
+
+use crate::config::CliConfig;
+
+pub async fn run(conf: UFMConfig, config: &CliConfig) -> Result<(), UFMError> {
+    let ufm = ufmclient::connect(conf)?;
+    let deviations = ufm
+        .check_qos_compliance(&config.profiles(), &config.assignments)
+        .await?;
+
+    if deviations.is_empty() {
+        println!("All partitions comply with their QoS profiles.");
+        return Ok(());
+    }
+
+    println!(
+        "{:<10}{:<20}{:<16}{:<24}{:<24}",
+        "Pkey", "Name", "Profile", "Expected(MTU/SL/Rate)", "Actual(MTU/SL/Rate)"
+    );
+    for d in deviations {
+        println!(
+            "{:<10}{:<20}{:<16}{:<24}{:<24}",
+            d.pkey,
+            d.name,
+            d.profile,
+            format!(
+                "{}/{}/{}",
+                d.expected.mtu_limit, d.expected.service_level, d.expected.rate_limit
+            ),
+            format!(
+                "{}/{}/{}",
+                d.actual.mtu_limit, d.actual.service_level, d.actual.rate_limit
+            ),
+        );
+    }
+
+    Ok(())
+}
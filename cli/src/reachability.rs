@@ -0,0 +1,69 @@
+use ufmclient::{is_guid, UFMConfig, UFMError, Ufm};
+
+pub struct ReachabilityOptions {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub hosts: Vec<String>,
+    pub format: String,
+}
+
+pub async fn run(conf: UFMConfig, opt: &ReachabilityOptions) -> Result<(), UFMError> {
+    let ufm = ufmclient::connect(conf)?;
+    let reachability = ufm.reachability().await?;
+
+    if let (Some(from), Some(to)) = (&opt.from, &opt.to) {
+        let from_guids = resolve(&ufm, from).await?;
+        let to_guids = resolve(&ufm, to).await?;
+
+        for a in &from_guids {
+            for b in &to_guids {
+                for (pkey, reason) in reachability.can_communicate(a, b) {
+                    println!("{:<20}{:<20}{:<10}{:<10}{}", a, b, pkey, "allowed", reason);
+                }
+                for (pkey, reason) in reachability.blocked(a, b) {
+                    println!("{:<20}{:<20}{:<10}{:<10}{}", a, b, pkey, "blocked", reason);
+                }
+            }
+        }
+
+        return Ok(());
+    }
+
+    if opt.hosts.is_empty() {
+        return Err(UFMError::InvalidConfig(
+            "--from/--to or --hosts is required".to_string(),
+        ));
+    }
+
+    let mut endpoints = vec![];
+    for h in &opt.hosts {
+        endpoints.push((h.to_string(), resolve(&ufm, h).await?));
+    }
+    let matrix = reachability.matrix(&endpoints);
+
+    match opt.format.to_lowercase().as_str() {
+        "csv" => print!("{}", matrix.to_csv()),
+        "json" => println!(
+            "{}",
+            serde_json::to_string_pretty(&matrix)
+                .map_err(|e| UFMError::InvalidConfig(e.to_string()))?
+        ),
+        f => return Err(UFMError::InvalidConfig(format!("unknown format {}", f))),
+    };
+
+    Ok(())
+}
+
+/// Resolve the GUID or hostname to the GUIDs.
+async fn resolve(ufm: &Ufm, target: &str) -> Result<Vec<String>, UFMError> {
+    if is_guid(target) {
+        return Ok(vec![target.to_string()]);
+    }
+
+    Ok(ufm
+        .resolve_hosts(&[target.to_string()])
+        .await?
+        .into_values()
+        .flatten()
+        .collect())
+}
@@ -0,0 +1,17 @@
+use ufmclient::{PartitionKey, UFMConfig, UFMError};
+
+pub async fn run(
+    conf: UFMConfig,
+    pkey: &str,
+    hosts: &[String],
+    force: bool,
+) -> Result<(), UFMError> {
+    let ufm = ufmclient::connect(conf)?;
+    let pkey = PartitionKey::try_from(pkey)?;
+    let changes = ufm.unbind_hosts(pkey, hosts.to_vec(), force).await?;
+
+    crate::ports::print_changes(&changes);
+    crate::print_dry_run(&ufm);
+
+    Ok(())
+}
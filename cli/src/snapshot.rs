@@ -0,0 +1,56 @@
+use std::fs;
+
+use ufmclient::{ImportMode, PartitionSnapshot, UFMConfig, UFMError};
+
+pub async fn export(conf: UFMConfig, file: &Option<String>, format: &str) -> Result<(), UFMError> {
+    let ufm = ufmclient::connect(conf)?;
+    let snapshot = ufm.export_partitions().await?;
+
+    let data = match format.to_lowercase().as_str() {
+        "yaml" => {
+            serde_yaml::to_string(&snapshot).map_err(|e| UFMError::InvalidConfig(e.to_string()))?
+        }
+        "json" => serde_json::to_string_pretty(&snapshot)
+            .map_err(|e| UFMError::InvalidConfig(e.to_string()))?,
+        _ => {
+            return Err(UFMError::InvalidConfig(format!(
+                "unknown format {}",
+                format
+            )))
+        }
+    };
+
+    match file {
+        Some(f) => fs::write(f, data)
+            .map_err(|e| UFMError::InvalidConfig(format!("failed to write {}: {}", f, e)))?,
+        None => println!("{}", data),
+    };
+
+    Ok(())
+}
+
+pub async fn import(conf: UFMConfig, file: &str, replace: bool) -> Result<(), UFMError> {
+    let ufm = ufmclient::connect(conf)?;
+
+    let data = fs::read_to_string(file)
+        .map_err(|e| UFMError::InvalidConfig(format!("failed to read {}: {}", file, e)))?;
+    let snapshot: PartitionSnapshot = serde_yaml::from_str(&data)
+        .map_err(|e| UFMError::InvalidConfig(format!("failed to parse {}: {}", file, e)))?;
+
+    let mode = match replace {
+        true => ImportMode::Replace,
+        false => ImportMode::Merge,
+    };
+    let plan = ufm.import_partitions(&snapshot, mode).await?;
+
+    for action in &plan.actions {
+        println!("{}", action);
+    }
+    crate::print_dry_run(&ufm);
+    match ufm.is_dry_run() {
+        true => println!("Dry run, no change was imported."),
+        false => println!("Imported {} change(s).", plan.actions.len()),
+    }
+
+    Ok(())
+}
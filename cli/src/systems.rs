@@ -0,0 +1,51 @@
+use ufmclient::{System, SystemFilter, UFMConfig, UFMError};
+
+fn opt(v: &Option<String>) -> &str {
+    v.as_deref().unwrap_or_default()
+}
+
+pub async fn list(conf: UFMConfig, filter: &SystemFilter) -> Result<(), UFMError> {
+    let ufm = ufmclient::connect(conf)?;
+    let systems = ufm.list_systems(filter).await?;
+
+    println!(
+        "{:<20}{:<30}{:<10}{:<16}{:<12}{:<20}{:<16}{:<10}{:<8}",
+        "GUID", "Name", "Type", "Model", "Vendor", "Firmware", "IP", "State", "Ports"
+    );
+    for s in systems {
+        println!(
+            "{:<20}{:<30}{:<10}{:<16}{:<12}{:<20}{:<16}{:<10}{:<8}",
+            s.guid,
+            s.name,
+            s.system_type,
+            opt(&s.model),
+            opt(&s.vendor),
+            opt(&s.firmware_version),
+            opt(&s.ip),
+            opt(&s.state),
+            s.port_count(),
+        );
+    }
+
+    Ok(())
+}
+
+pub async fn view(conf: UFMConfig, id: &str) -> Result<(), UFMError> {
+    let ufm = ufmclient::connect(conf)?;
+    let s: System = ufm.get_system(id).await?;
+
+    println!("{:15}: {}", "GUID", s.guid);
+    println!("{:15}: {}", "Name", s.name);
+    println!("{:15}: {}", "Type", s.system_type);
+    println!("{:15}: {}", "Model", opt(&s.model));
+    println!("{:15}: {}", "Vendor", opt(&s.vendor));
+    println!("{:15}: {}", "Firmware", opt(&s.firmware_version));
+    println!("{:15}: {}", "Software", opt(&s.software_version));
+    println!("{:15}: {}", "IP", opt(&s.ip));
+    println!("{:15}: {}", "State", opt(&s.state));
+    println!("{:15}: {}", "Rack", opt(&s.rack));
+    println!("{:15}: {}", "Description", opt(&s.description));
+    println!("{:15}: {}", "Ports", s.ports.join(", "));
+
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use std::fs;
+
+use ufmclient::{FabricDesign, PartitionKey, TopologyFilter, UFMConfig, UFMError};
+
+pub struct ExportOptions {
+    pub format: String,
+    pub rack: Option<String>,
+    pub tiers: Vec<i32>,
+    pub pkey: Option<String>,
+}
+
+pub async fn export(conf: UFMConfig, opt: &ExportOptions) -> Result<(), UFMError> {
+    let ufm = ufmclient::connect(conf)?;
+
+    // The systems of the members, as the GUIDs of virtual ports are not in the topology.
+    let systems = match &opt.pkey {
+        Some(pkey) => {
+            let pkey = PartitionKey::try_from(pkey.as_str())?;
+            let ports = ufm.list_port(pkey).await?;
+            Some(ports.into_iter().map(|p| p.system_id).collect())
+        }
+        None => None,
+    };
+    let filter = TopologyFilter {
+        rack: opt.rack.clone(),
+        tiers: opt.tiers.clone(),
+        systems,
+    };
+    let graph = ufm.topology().await?.filter(&filter).graph();
+
+    match opt.format.to_lowercase().as_str() {
+        "dot" => print!("{}", graph.to_dot()),
+        "graphml" => print!("{}", graph.to_graphml()),
+        "json" => println!(
+            "{}",
+            serde_json::to_string_pretty(&graph)
+                .map_err(|e| UFMError::InvalidConfig(e.to_string()))?
+        ),
+        f => return Err(UFMError::InvalidConfig(format!("unknown format {}", f))),
+    };
+
+    Ok(())
+}
+
+pub async fn validate(conf: UFMConfig, design: &str, format: &str) -> Result<(), UFMError> {
+    let data = fs::read_to_string(design)
+        .map_err(|e| UFMError::InvalidConfig(format!("failed to read {}: {}", design, e)))?;
+    let design: FabricDesign = serde_yaml::from_str(&data)
+        .map_err(|e| UFMError::InvalidConfig(format!("failed to parse {}: {}", design, e)))?;
+
+    let ufm = ufmclient::connect(conf)?;
+    let report = ufm.validate_fat_tree(&design).await?;
+
+    match format.to_lowercase().as_str() {
+        "text" => {
+            if report.is_valid() {
+                println!("The fabric matches the design.");
+            }
+            for issue in &report.issues {
+                println!("{}", issue);
+            }
+        }
+        "json" => println!(
+            "{}",
+            serde_json::to_string_pretty(&report)
+                .map_err(|e| UFMError::InvalidConfig(e.to_string()))?
+        ),
+        "yaml" => print!(
+            "{}",
+            serde_yaml::to_string(&report).map_err(|e| UFMError::InvalidConfig(e.to_string()))?
+        ),
+        f => return Err(UFMError::InvalidConfig(format!("unknown format {}", f))),
+    };
+
+    Ok(())
+}
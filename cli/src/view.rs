@@ -1,12 +1,12 @@
 use ufmclient::{UFMConfig, UFMError};
 
-pub async fn run(conf: UFMConfig, pkey: &String) -> Result<(), UFMError> {
+pub async fn run(conf: UFMConfig, pkey: &str) -> Result<(), UFMError> {
     let ufm = ufmclient::connect(conf)?;
     let p = ufm.get_partition(pkey).await?;
     let ps = ufm.list_port(p.pkey).await?;
 
     println!("{:15}: {}", "Name", p.name);
-    println!("{:15}: {}", "Pkey", p.pkey.to_string());
+    println!("{:15}: {}", "Pkey", p.pkey);
     println!("{:15}: {}", "IPoIB", p.ipoib);
     println!("{:15}: {}", "MTU", p.qos.mtu_limit);
     println!("{:15}: {}", "Rate Limit", p.qos.rate_limit);
@@ -18,7 +18,7 @@ pub async fn run(conf: UFMConfig, pkey: &String) -> Result<(), UFMError> {
         "GUID", "ParentGUID", "PortType", "SystemID", "LID", "SystemName", "LogState", "Name",
     );
     for port in ps {
-        println!("{}", port.to_string());
+        println!("{}", port);
     }
 
     Ok(())
@@ -0,0 +1,23 @@
+use ufmclient::{is_guid, UFMConfig, UFMError};
+
+pub async fn run(conf: UFMConfig, target: &str) -> Result<(), UFMError> {
+    let ufm = ufmclient::connect(conf)?;
+
+    let ms = match is_guid(target) {
+        true => ufm.partitions_for_guid(target).await?,
+        false => ufm.partitions_for_system(target).await?,
+    };
+
+    println!(
+        "{:<20}{:<10}{:<20}{:<12}{:<10}",
+        "GUID", "Pkey", "Name", "Membership", "Index0"
+    );
+    for m in ms {
+        println!(
+            "{:<20}{:<10}{:<20}{:<12}{:<10}",
+            m.guid, m.pkey, m.name, m.membership, m.index0
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,314 @@
+use std::collections::HashSet;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Partition, PartitionKey, UFMError, Ufm};
+
+/// The times to retry when the allocated pkey is taken by a concurrent allocation.
+const ALLOCATE_RETRIES: usize = 5;
+
+/// The inclusive range of pkeys, e.g. "0x100-0x7ff".
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(try_from = "RangeBounds")]
+pub struct PartitionKeyRange {
+    pub start: PartitionKey,
+    pub end: PartitionKey,
+}
+
+/// The bounds of a range as deserialized, which are checked by `PartitionKeyRange::new`.
+#[derive(Deserialize)]
+struct RangeBounds {
+    start: PartitionKey,
+    end: PartitionKey,
+}
+
+impl TryFrom<RangeBounds> for PartitionKeyRange {
+    type Error = UFMError;
+
+    fn try_from(b: RangeBounds) -> Result<Self, Self::Error> {
+        PartitionKeyRange::new(b.start, b.end)
+    }
+}
+
+impl PartitionKeyRange {
+    pub fn new(start: PartitionKey, end: PartitionKey) -> Result<Self, UFMError> {
+        if start > end {
+            return Err(UFMError::InvalidConfig(format!(
+                "invalid pkey range {}-{}",
+                start, end
+            )));
+        }
+
+        Ok(PartitionKeyRange { start, end })
+    }
+
+    pub fn contains(&self, pkey: &PartitionKey) -> bool {
+        self.start <= *pkey && *pkey <= self.end
+    }
+
+    /// Iterate the pkeys in the range except the default pkey.
+    pub fn iter(&self) -> impl Iterator<Item = PartitionKey> {
+        let (start, end): (i32, i32) = (self.start.into(), self.end.into());
+        (start..=end)
+            .filter_map(|k| PartitionKey::try_from(k).ok())
+            .filter(|k| !k.is_default_pkey())
+    }
+}
+
+impl FromStr for PartitionKeyRange {
+    type Err = UFMError;
+
+    fn from_str(range: &str) -> Result<Self, Self::Err> {
+        let (start, end) = range
+            .split_once('-')
+            .ok_or(UFMError::InvalidConfig(format!(
+                "invalid pkey range {}",
+                range
+            )))?;
+
+        PartitionKeyRange::new(PartitionKey::from_str(start)?, PartitionKey::from_str(end)?)
+    }
+}
+
+impl Display for PartitionKeyRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.start, self.end)
+    }
+}
+
+impl Ufm {
+    /// Create a partition from the template with the lowest free pkey in the range; the
+    /// pkey of the template is ignored. The name of the template is required, and should
+    /// be unique: the partition is read back after creating it, and if it has another
+    /// name, the pkey was taken by a concurrent allocation and the next free one is tried.
+    /// A failed create is only retried if the pkey exists with another name, too.
+    pub async fn allocate_partition(
+        &self,
+        range: PartitionKeyRange,
+        template: &Partition,
+    ) -> Result<Partition, UFMError> {
+        if template.name.is_empty() {
+            return Err(UFMError::InvalidConfig(
+                "the name of the allocated partition is required".to_string(),
+            ));
+        }
+
+        let mut used: HashSet<PartitionKey> = self
+            .list_partition()
+            .await?
+            .into_iter()
+            .map(|p| p.pkey)
+            .collect();
+
+        for _ in 0..ALLOCATE_RETRIES {
+            let pkey = range
+                .iter()
+                .find(|k| !used.contains(k))
+                .ok_or(UFMError::InvalidConfig(format!(
+                    "no free pkey in range {}",
+                    range
+                )))?;
+
+            let p = Partition {
+                pkey,
+                ..template.clone()
+            };
+
+            let created = self.create_partition(&p).await;
+            // Nothing is created in dry-run mode, so there is nothing to read back.
+            if created.is_ok() && self.is_dry_run() {
+                return Ok(p);
+            }
+
+            match (created, self.get_partition(&pkey.to_string()).await) {
+                (_, Ok(live)) if live.name != p.name => {
+                    log::info!(
+                        "pkey {} was allocated concurrently as {}, retry",
+                        pkey,
+                        live.name
+                    );
+                    used.insert(pkey);
+                }
+                (Ok(()), Ok(_)) => return Ok(p),
+                (Ok(()), Err(e)) | (Err(e), _) => return Err(e),
+            }
+        }
+
+        Err(UFMError::Unknown(format!(
+            "failed to allocate pkey in range {} after {} retries",
+            range, ALLOCATE_RETRIES
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockUfm;
+    use crate::PartitionQoS;
+
+    fn pkey(k: i32) -> PartitionKey {
+        PartitionKey::try_from(k).unwrap()
+    }
+
+    fn template() -> Partition {
+        Partition {
+            name: "tenant01".to_string(),
+            pkey: crate::DEFAULT_PKEY,
+            ipoib: true,
+            qos: PartitionQoS::default(),
+        }
+    }
+
+    fn pkey_json(name: &str) -> String {
+        format!(
+            r#"{{"partition": "{}", "ip_over_ib": true, "qos_conf": {{"mtu_limit": 2, "service_level": 0, "rate_limit": 100}}}}"#,
+            name
+        )
+    }
+
+    #[test]
+    fn parse_range() {
+        let range = PartitionKeyRange::from_str("0x100-0x7FF").unwrap();
+        assert_eq!(
+            range,
+            PartitionKeyRange::new(pkey(0x100), pkey(0x7ff)).unwrap()
+        );
+        assert_eq!(range.to_string(), "0x100-0x7ff");
+        assert!(range.contains(&pkey(0x100)));
+        assert!(!range.contains(&pkey(0x800)));
+    }
+
+    #[test]
+    fn parse_invalid_range() {
+        assert!(PartitionKeyRange::from_str("0x7ff-0x100").is_err());
+        assert!(PartitionKeyRange::from_str("0x0-0x100").is_err());
+        assert!(PartitionKeyRange::from_str("0x100").is_err());
+        assert!(PartitionKeyRange::from_str("0x100-0x8000").is_err());
+    }
+
+    #[test]
+    fn deserialize_invalid_range() {
+        let range: Result<PartitionKeyRange, _> =
+            serde_json::from_str(r#"{"start": "0x7ff", "end": "0x100"}"#);
+        assert!(range.is_err());
+
+        let range: PartitionKeyRange =
+            serde_json::from_str(r#"{"start": "0x100", "end": "0x7ff"}"#).unwrap();
+        assert_eq!(range.end, pkey(0x7ff));
+    }
+
+    #[test]
+    fn iterate_without_default_pkey() {
+        let range = PartitionKeyRange::from_str("0x7ffe-0x7fff").unwrap();
+        assert_eq!(range.iter().collect::<Vec<_>>(), vec![pkey(0x7ffe)]);
+    }
+
+    #[tokio::test]
+    async fn allocate_lowest_free_pkey() {
+        let mock = MockUfm::start().await;
+        mock.on(
+            "GET",
+            "/resources/pkeys?qos_conf=true",
+            200,
+            &format!(r#"{{"0x100": {}}}"#, pkey_json("other")),
+        )
+        .on("POST", "/resources/pkeys/add", 200, "")
+        .on(
+            "GET",
+            "/resources/pkeys/0x101?qos_conf=true",
+            200,
+            &pkey_json("tenant01"),
+        );
+
+        let range = PartitionKeyRange::from_str("0x100-0x102").unwrap();
+        let p = mock
+            .ufm()
+            .allocate_partition(range, &template())
+            .await
+            .unwrap();
+
+        assert_eq!(p.pkey, pkey(0x101));
+        assert!(p.ipoib);
+    }
+
+    #[tokio::test]
+    async fn retry_pkey_allocated_concurrently() {
+        let mock = MockUfm::start().await;
+        mock.on("GET", "/resources/pkeys?qos_conf=true", 200, "{}")
+            .on("POST", "/resources/pkeys/add", 200, "")
+            // The create of 0x100 updated the partition of the concurrent allocation.
+            .on(
+                "GET",
+                "/resources/pkeys/0x100?qos_conf=true",
+                200,
+                &pkey_json("other"),
+            )
+            .on(
+                "GET",
+                "/resources/pkeys/0x101?qos_conf=true",
+                200,
+                &pkey_json("tenant01"),
+            );
+
+        let range = PartitionKeyRange::from_str("0x100-0x102").unwrap();
+        let p = mock
+            .ufm()
+            .allocate_partition(range, &template())
+            .await
+            .unwrap();
+
+        assert_eq!(p.pkey, pkey(0x101));
+        assert_eq!(mock.changes().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn fail_create_error() {
+        let mock = MockUfm::start().await;
+        mock.on("GET", "/resources/pkeys?qos_conf=true", 200, "{}")
+            .on("POST", "/resources/pkeys/add", 500, "internal error");
+
+        let range = PartitionKeyRange::from_str("0x100-0x102").unwrap();
+        let res = mock.ufm().allocate_partition(range, &template()).await;
+
+        assert!(res.is_err());
+        assert_eq!(mock.changes().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn no_free_pkey() {
+        let mock = MockUfm::start().await;
+        mock.on(
+            "GET",
+            "/resources/pkeys?qos_conf=true",
+            200,
+            &format!(
+                r#"{{"0x100": {}, "0x101": {}}}"#,
+                pkey_json("a"),
+                pkey_json("b")
+            ),
+        );
+
+        let range = PartitionKeyRange::from_str("0x100-0x101").unwrap();
+        let res = mock.ufm().allocate_partition(range, &template()).await;
+
+        assert!(matches!(res, Err(UFMError::InvalidConfig(m)) if m.contains("no free pkey")));
+        assert!(mock.changes().is_empty());
+    }
+
+    #[tokio::test]
+    async fn require_name() {
+        let mock = MockUfm::start().await;
+        let mut template = template();
+        template.name = String::new();
+
+        let range = PartitionKeyRange::from_str("0x100-0x101").unwrap();
+        assert!(mock
+            .ufm()
+            .allocate_partition(range, &template)
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,210 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{MembershipIndex, PartitionKey, PortMembership, UFMError, Ufm};
+
+/// The partition memberships of all GUIDs, to check whether two GUIDs can talk to each
+/// other: two members of a partition can communicate unless both of them are limited.
+pub struct Reachability {
+    index: MembershipIndex,
+}
+
+/// The reachability between the endpoints, e.g. hosts; an endpoint reaches another one
+/// if any of its GUIDs can communicate with any GUID of the other.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReachabilityMatrix {
+    /// The names of the endpoints, in the order of the rows and columns.
+    pub endpoints: Vec<String>,
+    /// The pkeys through which endpoint `i` reaches endpoint `j`; empty if unreachable.
+    pub pkeys: Vec<Vec<Vec<PartitionKey>>>,
+}
+
+impl Reachability {
+    pub fn new(index: MembershipIndex) -> Self {
+        Reachability { index }
+    }
+
+    /// List the partitions through which the GUIDs can communicate, with the reason.
+    pub fn can_communicate(&self, a: &str, b: &str) -> Vec<(PartitionKey, String)> {
+        self.shared(a, b)
+            .into_iter()
+            .filter(|(_, ma, mb)| *ma == PortMembership::Full || *mb == PortMembership::Full)
+            .map(|(pkey, ma, mb)| (pkey, format!("{} is {}, {} is {}", a, ma, b, mb)))
+            .collect()
+    }
+
+    /// List the partitions shared by the GUIDs in which they can not communicate, with the reason.
+    pub fn blocked(&self, a: &str, b: &str) -> Vec<(PartitionKey, String)> {
+        self.shared(a, b)
+            .into_iter()
+            .filter(|(_, ma, mb)| *ma == PortMembership::Limited && *mb == PortMembership::Limited)
+            .map(|(pkey, _, _)| (pkey, format!("both {} and {} are limited", a, b)))
+            .collect()
+    }
+
+    /// Build the reachability matrix of the endpoints, each of them is a name with its GUIDs.
+    pub fn matrix(&self, endpoints: &[(String, Vec<String>)]) -> ReachabilityMatrix {
+        let mut pkeys = vec![];
+        for (_, from) in endpoints {
+            let mut row = vec![];
+            for (_, to) in endpoints {
+                let mut cell: Vec<PartitionKey> = from
+                    .iter()
+                    .flat_map(|a| to.iter().map(move |b| (a, b)))
+                    .flat_map(|(a, b)| self.can_communicate(a, b))
+                    .map(|(pkey, _)| pkey)
+                    .collect();
+                cell.sort();
+                cell.dedup();
+                row.push(cell);
+            }
+            pkeys.push(row);
+        }
+
+        ReachabilityMatrix {
+            endpoints: endpoints.iter().map(|(n, _)| n.clone()).collect(),
+            pkeys,
+        }
+    }
+
+    fn shared(&self, a: &str, b: &str) -> Vec<(PartitionKey, PortMembership, PortMembership)> {
+        let ma: BTreeMap<PartitionKey, PortMembership> = self
+            .index
+            .get(a)
+            .into_iter()
+            .map(|m| (m.pkey, m.membership))
+            .collect();
+
+        self.index
+            .get(b)
+            .into_iter()
+            .filter_map(|m| ma.get(&m.pkey).map(|x| (m.pkey, x.clone(), m.membership)))
+            .collect()
+    }
+}
+
+impl ReachabilityMatrix {
+    /// Export the matrix as CSV; each cell lists the pkeys separated by ';'.
+    pub fn to_csv(&self) -> String {
+        let mut csv = format!(",{}\n", self.endpoints.join(","));
+        for (name, row) in self.endpoints.iter().zip(&self.pkeys) {
+            let cells: Vec<String> = row
+                .iter()
+                .map(|cell| {
+                    cell.iter()
+                        .map(|k| k.to_string())
+                        .collect::<Vec<_>>()
+                        .join(";")
+                })
+                .collect();
+            csv.push_str(&format!("{},{}\n", name, cells.join(",")));
+        }
+
+        csv
+    }
+}
+
+impl Ufm {
+    /// Build the reachability of all GUIDs from the memberships of all partitions.
+    pub async fn reachability(&self) -> Result<Reachability, UFMError> {
+        Ok(Reachability::new(self.membership_index().await?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GuidMembership;
+
+    fn reachability(members: &[(&str, i32, PortMembership)]) -> Reachability {
+        let mut index = MembershipIndex::default();
+        for (guid, pkey, membership) in members {
+            index.insert(GuidMembership {
+                guid: guid.to_string(),
+                pkey: PartitionKey::try_from(*pkey).unwrap(),
+                name: format!("p{}", pkey),
+                ipoib: false,
+                membership: membership.clone(),
+                index0: false,
+            });
+        }
+
+        Reachability::new(index)
+    }
+
+    fn pkey(pkey: i32) -> PartitionKey {
+        PartitionKey::try_from(pkey).unwrap()
+    }
+
+    #[test]
+    fn full_full() {
+        let r = reachability(&[
+            ("a", 0x10, PortMembership::Full),
+            ("b", 0x10, PortMembership::Full),
+        ]);
+        let pkeys: Vec<_> = r
+            .can_communicate("a", "b")
+            .into_iter()
+            .map(|(k, _)| k)
+            .collect();
+        assert_eq!(pkeys, vec![pkey(0x10)]);
+        assert!(r.blocked("a", "b").is_empty());
+    }
+
+    #[test]
+    fn full_limited() {
+        let r = reachability(&[
+            ("a", 0x10, PortMembership::Full),
+            ("b", 0x10, PortMembership::Limited),
+        ]);
+        assert_eq!(r.can_communicate("a", "b").len(), 1);
+        assert_eq!(r.can_communicate("b", "a").len(), 1);
+        assert!(r.blocked("a", "b").is_empty());
+    }
+
+    #[test]
+    fn limited_limited() {
+        let r = reachability(&[
+            ("a", 0x10, PortMembership::Limited),
+            ("b", 0x10, PortMembership::Limited),
+        ]);
+        assert!(r.can_communicate("a", "b").is_empty());
+        let pkeys: Vec<_> = r.blocked("a", "b").into_iter().map(|(k, _)| k).collect();
+        assert_eq!(pkeys, vec![pkey(0x10)]);
+    }
+
+    #[test]
+    fn no_shared_pkey() {
+        let r = reachability(&[
+            ("a", 0x10, PortMembership::Full),
+            ("b", 0x20, PortMembership::Full),
+        ]);
+        assert!(r.can_communicate("a", "b").is_empty());
+        assert!(r.blocked("a", "b").is_empty());
+    }
+
+    #[test]
+    fn matrix() {
+        let r = reachability(&[
+            ("a1", 0x10, PortMembership::Full),
+            ("a2", 0x20, PortMembership::Limited),
+            ("b1", 0x10, PortMembership::Limited),
+            ("b1", 0x20, PortMembership::Limited),
+            ("c1", 0x30, PortMembership::Full),
+        ]);
+        let endpoints = vec![
+            ("a".to_string(), vec!["a1".to_string(), "a2".to_string()]),
+            ("b".to_string(), vec!["b1".to_string()]),
+            ("c".to_string(), vec!["c1".to_string()]),
+        ];
+        let m = r.matrix(&endpoints);
+
+        assert_eq!(m.endpoints, vec!["a", "b", "c"]);
+        assert_eq!(m.pkeys[0][1], vec![pkey(0x10)]);
+        assert_eq!(m.pkeys[1][0], vec![pkey(0x10)]);
+        assert!(m.pkeys[0][2].is_empty());
+        assert!(m.pkeys[1][1].is_empty());
+        assert_eq!(m.pkeys[2][2], vec![pkey(0x30)]);
+    }
+}
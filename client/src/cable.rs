@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+
+use crate::lookup::normalize_guid;
+use crate::{UFMError, Ufm};
+
+/// The cable and transceiver of a link, with the ports on both sides.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Cable {
+    #[serde(default)]
+    pub identifier: Option<String>,
+    #[serde(default)]
+    pub vendor: Option<String>,
+    #[serde(default)]
+    pub part_number: Option<String>,
+    #[serde(default)]
+    pub serial_number: Option<String>,
+    /// The length of the cable, e.g. "3 m".
+    #[serde(default)]
+    pub length: Option<String>,
+    /// The technology of the cable, e.g. "Copper cable- unequalized", "850 nm VCSEL".
+    #[serde(default)]
+    pub technology: Option<String>,
+    #[serde(default)]
+    pub revision: Option<String>,
+    #[serde(default, rename = "fw_version")]
+    pub firmware_version: Option<String>,
+    /// The temperature of the transceiver, e.g. "45C"; only reported by the active cables.
+    #[serde(default)]
+    pub temperature: Option<String>,
+    /// The optical power of the lanes in dBm; only reported by the optical cables.
+    #[serde(default)]
+    pub tx_power: Option<String>,
+    #[serde(default)]
+    pub rx_power: Option<String>,
+    /// The name of the port on the source side, e.g. "1070fd0300176624_2".
+    #[serde(default, rename = "source_port")]
+    pub port: String,
+    #[serde(default, rename = "source_guid")]
+    pub port_guid: String,
+    #[serde(default, rename = "source_port_dname")]
+    pub port_dname: Option<String>,
+    #[serde(default, rename = "destination_port")]
+    pub peer_port: String,
+    #[serde(default, rename = "destination_guid")]
+    pub peer_guid: String,
+    #[serde(default, rename = "destination_port_dname")]
+    pub peer_port_dname: Option<String>,
+}
+
+impl Cable {
+    /// Whether the port, which is a GUID or name, is on either side of the cable.
+    pub fn connects(&self, port: &str) -> bool {
+        let guid = normalize_guid(port);
+        self.port == port
+            || self.peer_port == port
+            || normalize_guid(&self.port_guid) == guid
+            || normalize_guid(&self.peer_guid) == guid
+    }
+}
+
+impl Ufm {
+    pub async fn list_cables(&self) -> Result<Vec<Cable>, UFMError> {
+        let path = String::from("/resources/cables");
+        let cables: Vec<Cable> = self.client.list(&path).await?;
+
+        Ok(cables)
+    }
+
+    /// Get the cable of the port, which is a GUID or name; only the cables of the
+    /// system of the port are queried, and they are also filtered by the client.
+    pub async fn get_cable(&self, port: &str) -> Result<Cable, UFMError> {
+        let target = self
+            .resolve_ports(&[port.to_string()])
+            .await?
+            .pop()
+            .ok_or(UFMError::NotFound(port.to_string()))?;
+
+        let mut query = url::form_urlencoded::Serializer::new(String::new());
+        query.append_pair("system", &target.system_id);
+        let path = format!("/resources/cables?{}", query.finish());
+        let cables: Vec<Cable> = self.client.list(&path).await?;
+
+        cables
+            .into_iter()
+            .find(|c| c.connects(&target.name) || c.connects(&target.guid))
+            .ok_or(UFMError::NotFound(format!("cable of {}", port)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_cable() {
+        let cable: Cable = serde_json::from_str(
+            r#"{
+                "fw_version": "46.120.0",
+                "source_port": "1070fd0300176624_2",
+                "source_guid": "1070fd0300176625",
+                "destination_port": "b8599f0300fc6de4_1",
+                "destination_guid": "b8599f0300fc6de4"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(cable.firmware_version.as_deref(), Some("46.120.0"));
+        assert!(cable.connects("1070fd0300176624_2"));
+        assert!(cable.connects("0xB8599F0300FC6DE4"));
+        assert!(!cable.connects("1070fd0300176626"));
+    }
+}
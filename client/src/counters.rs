@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{PortAction, PortActionJob, UFMError, Ufm};
+
+/// The performance and error counters of a port.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct PortCounters {
+    /// The name of the port, e.g. "1070fd0300176624_2".
+    pub port: String,
+    pub guid: String,
+    pub xmit_data: u64,
+    pub rcv_data: u64,
+    pub xmit_packets: u64,
+    pub rcv_packets: u64,
+    pub symbol_errors: u64,
+    pub link_downed: u64,
+    pub link_error_recovery: u64,
+    pub rcv_errors: u64,
+    pub rcv_remote_physical_errors: u64,
+    pub rcv_switch_relay_errors: u64,
+    pub xmit_discards: u64,
+    pub xmit_constraint_errors: u64,
+    pub rcv_constraint_errors: u64,
+    pub local_link_integrity_errors: u64,
+    pub excessive_buffer_overrun_errors: u64,
+    pub vl15_dropped: u64,
+    pub xmit_wait: u64,
+}
+
+/// The attributes of the monitoring API, in the order of the counters.
+const COUNTER_ATTRIBUTES: [&str; 17] = [
+    "Infiniband_XmitData",
+    "Infiniband_RcvData",
+    "Infiniband_XmitPkts",
+    "Infiniband_RcvPkts",
+    "Infiniband_SymbolErrorCounter",
+    "Infiniband_LinkDownedCounter",
+    "Infiniband_LinkErrorRecoveryCounter",
+    "Infiniband_PortRcvErrors",
+    "Infiniband_PortRcvRemotePhysicalErrors",
+    "Infiniband_PortRcvSwitchRelayErrors",
+    "Infiniband_PortXmitDiscards",
+    "Infiniband_PortXmitConstraintErrors",
+    "Infiniband_PortRcvConstraintErrors",
+    "Infiniband_LocalLinkIntegrityErrors",
+    "Infiniband_ExcessiveBufferOverrunErrors",
+    "Infiniband_VL15Dropped",
+    "Infiniband_PortXmitWait",
+];
+
+impl PortCounters {
+    /// The error counters which are not zero, by name.
+    pub fn errors(&self) -> Vec<(&'static str, u64)> {
+        [
+            ("symbol_errors", self.symbol_errors),
+            ("link_downed", self.link_downed),
+            ("link_error_recovery", self.link_error_recovery),
+            ("rcv_errors", self.rcv_errors),
+            (
+                "rcv_remote_physical_errors",
+                self.rcv_remote_physical_errors,
+            ),
+            ("rcv_switch_relay_errors", self.rcv_switch_relay_errors),
+            ("xmit_discards", self.xmit_discards),
+            ("xmit_constraint_errors", self.xmit_constraint_errors),
+            ("rcv_constraint_errors", self.rcv_constraint_errors),
+            (
+                "local_link_integrity_errors",
+                self.local_link_integrity_errors,
+            ),
+            (
+                "excessive_buffer_overrun_errors",
+                self.excessive_buffer_overrun_errors,
+            ),
+            ("vl15_dropped", self.vl15_dropped),
+        ]
+        .into_iter()
+        .filter(|(_, v)| *v > 0)
+        .collect()
+    }
+
+    fn set(&mut self, attr: &str, v: u64) {
+        let counter = match attr {
+            "Infiniband_XmitData" => &mut self.xmit_data,
+            "Infiniband_RcvData" => &mut self.rcv_data,
+            "Infiniband_XmitPkts" => &mut self.xmit_packets,
+            "Infiniband_RcvPkts" => &mut self.rcv_packets,
+            "Infiniband_SymbolErrorCounter" => &mut self.symbol_errors,
+            "Infiniband_LinkDownedCounter" => &mut self.link_downed,
+            "Infiniband_LinkErrorRecoveryCounter" => &mut self.link_error_recovery,
+            "Infiniband_PortRcvErrors" => &mut self.rcv_errors,
+            "Infiniband_PortRcvRemotePhysicalErrors" => &mut self.rcv_remote_physical_errors,
+            "Infiniband_PortRcvSwitchRelayErrors" => &mut self.rcv_switch_relay_errors,
+            "Infiniband_PortXmitDiscards" => &mut self.xmit_discards,
+            "Infiniband_PortXmitConstraintErrors" => &mut self.xmit_constraint_errors,
+            "Infiniband_PortRcvConstraintErrors" => &mut self.rcv_constraint_errors,
+            "Infiniband_LocalLinkIntegrityErrors" => &mut self.local_link_integrity_errors,
+            "Infiniband_ExcessiveBufferOverrunErrors" => &mut self.excessive_buffer_overrun_errors,
+            "Infiniband_VL15Dropped" => &mut self.vl15_dropped,
+            "Infiniband_PortXmitWait" => &mut self.xmit_wait,
+            _ => return,
+        };
+        *counter = v;
+    }
+}
+
+/// The monitoring snapshot, keyed by the timestamp, then by the object type and the port name.
+type Snapshot = HashMap<String, HashMap<String, HashMap<String, HashMap<String, CounterValue>>>>;
+
+/// A number in the snapshot; the counters are read as integers to keep all their bits,
+/// and only the real floats are read as f64.
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(untagged)]
+enum CounterNumber {
+    Int(u64),
+    Float(f64),
+}
+
+impl CounterNumber {
+    fn value(&self) -> u64 {
+        match self {
+            CounterNumber::Int(v) => *v,
+            CounterNumber::Float(v) => *v as u64,
+        }
+    }
+}
+
+/// The value of an attribute in the snapshot, either raw or by function, e.g. {"RAW": 10}.
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum CounterValue {
+    Raw(CounterNumber),
+    Functions(HashMap<String, CounterNumber>),
+}
+
+impl CounterValue {
+    fn value(&self) -> u64 {
+        match self {
+            CounterValue::Raw(v) => v.value(),
+            CounterValue::Functions(f) => f.get("RAW").map(|v| v.value()).unwrap_or_default(),
+        }
+    }
+}
+
+impl Ufm {
+    /// Read the counters of the ports, which are GUIDs or names, from the monitoring snapshot.
+    pub async fn port_counters(&self, targets: &[String]) -> Result<Vec<PortCounters>, UFMError> {
+        let path = String::from("/monitoring/snapshot");
+        let ports = self.resolve_ports(targets).await?;
+
+        #[derive(Serialize, Deserialize, Debug)]
+        struct SnapshotQuery {
+            scope_object: String,
+            monitor_object: String,
+            objects: Vec<String>,
+            attributes: Vec<String>,
+            functions: Vec<String>,
+        }
+
+        let data = serde_json::to_string(&SnapshotQuery {
+            scope_object: "port".to_string(),
+            monitor_object: "port".to_string(),
+            objects: ports.iter().map(|p| p.name.clone()).collect(),
+            attributes: COUNTER_ATTRIBUTES.iter().map(|a| a.to_string()).collect(),
+            functions: vec!["RAW".to_string()],
+        })
+        .map_err(|_| UFMError::InvalidConfig("invalid monitoring snapshot".to_string()))?;
+
+        let snapshot: Snapshot = self.client.query(&path, data).await?;
+        let mut values: HashMap<String, HashMap<String, CounterValue>> = HashMap::new();
+        for (_, objects) in snapshot {
+            for (_, by_port) in objects {
+                values.extend(by_port);
+            }
+        }
+
+        let mut res = vec![];
+        for port in ports {
+            let mut counters = PortCounters {
+                port: port.name.clone(),
+                guid: port.guid.clone(),
+                ..PortCounters::default()
+            };
+            if let Some(attrs) = values.get(&port.name) {
+                for (attr, v) in attrs {
+                    counters.set(attr, v.value());
+                }
+            }
+            res.push(counters);
+        }
+
+        Ok(res)
+    }
+
+    /// Clear the counters of the ports, which are GUIDs or names, and return the jobs.
+    pub async fn reset_port_counters(
+        &self,
+        targets: &[String],
+    ) -> Result<Vec<PortActionJob>, UFMError> {
+        let ports = self.resolve_ports(targets).await?;
+        self.port_action(PortAction::ClearCounters, &ports, false)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(data: &str) -> u64 {
+        serde_json::from_str::<CounterValue>(data).unwrap().value()
+    }
+
+    #[test]
+    fn counter_values() {
+        assert_eq!(value("10"), 10);
+        assert_eq!(value("10.0"), 10);
+        assert_eq!(value(r#"{"RAW": 10}"#), 10);
+        assert_eq!(value(r#"{"AVG": 1.5}"#), 0);
+    }
+
+    #[test]
+    fn large_counter_values() {
+        assert_eq!(value("18446744073709551615"), u64::MAX);
+        assert_eq!(value(r#"{"RAW": 9007199254740993}"#), 9_007_199_254_740_993);
+    }
+}
@@ -0,0 +1,229 @@
+use std::collections::{BTreeMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    GuidMembership, Partition, PartitionKey, PartitionQoS, PortConfig, TransactionReport, UFMError,
+    Ufm,
+};
+
+/// The partition memberships of the drained GUIDs, which are used to restore them.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct DrainRecord {
+    pub guids: Vec<String>,
+    pub memberships: Vec<GuidMembership>,
+}
+
+/// Build the error of a failed transaction, with the steps which were not reverted
+/// by the rollback and need to be fixed by hand.
+fn transaction_error(action: &str, report: &TransactionReport) -> Option<UFMError> {
+    let failed = report.failed.as_ref()?;
+    let mut msg = format!("failed to {} '{}': {}", action, failed.step, failed.error);
+    if !report.not_reverted.is_empty() {
+        let steps: Vec<String> = report
+            .not_reverted
+            .iter()
+            .map(|s| format!("'{}': {}", s.step, s.error))
+            .collect();
+        msg.push_str(&format!("; not reverted: {}", steps.join(", ")));
+    }
+
+    Some(UFMError::Unknown(msg))
+}
+
+impl Ufm {
+    /// Build the record of the memberships to drain from the GUIDs, i.e. all their
+    /// partitions except the default pkey; nothing is changed.
+    pub async fn drain_record(&self, guids: Vec<String>) -> Result<DrainRecord, UFMError> {
+        let index = self.membership_index().await?;
+
+        let memberships: Vec<GuidMembership> = guids
+            .iter()
+            .flat_map(|g| index.get(g))
+            .filter(|m| !m.pkey.is_default_pkey())
+            .collect();
+
+        Ok(DrainRecord { guids, memberships })
+    }
+
+    /// Remove the GUIDs from the partitions of the record, except the default pkey if
+    /// the record has it; nothing is changed if any partition fails, unless the rollback
+    /// fails too, in which case the error lists the steps which were not reverted.
+    pub async fn drain(&self, record: &DrainRecord) -> Result<(), UFMError> {
+        let mut pkeys: BTreeMap<PartitionKey, Vec<String>> = BTreeMap::new();
+        for m in record
+            .memberships
+            .iter()
+            .filter(|m| !m.pkey.is_default_pkey())
+        {
+            pkeys.entry(m.pkey).or_default().push(m.guid.clone());
+        }
+
+        let mut tx = self.transaction();
+        for (pkey, guids) in pkeys {
+            tx = tx.unbind(pkey, guids);
+        }
+
+        match transaction_error("drain", &tx.commit().await) {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    /// Remove the GUIDs from all their partitions except the default pkey, and return
+    /// the memberships to restore them; see `drain` for the failure.
+    pub async fn drain_guids(&self, guids: Vec<String>) -> Result<DrainRecord, UFMError> {
+        let record = self.drain_record(guids).await?;
+        self.drain(&record).await?;
+
+        Ok(record)
+    }
+
+    /// Add the drained GUIDs back to their partitions with the same membership and index0;
+    /// see `drain` for the failure. The record does not have the name and QoS of the
+    /// partitions, so nothing is restored if any of them was deleted after the drain.
+    pub async fn restore(&self, record: &DrainRecord) -> Result<(), UFMError> {
+        let mut pkeys: BTreeMap<PartitionKey, (bool, Vec<PortConfig>)> = BTreeMap::new();
+        for m in record
+            .memberships
+            .iter()
+            .filter(|m| !m.pkey.is_default_pkey())
+        {
+            pkeys
+                .entry(m.pkey)
+                .or_insert((m.ipoib, vec![]))
+                .1
+                .push(PortConfig {
+                    guid: m.guid.clone(),
+                    index0: m.index0,
+                    membership: m.membership.clone(),
+                });
+        }
+
+        if pkeys.is_empty() {
+            return Ok(());
+        }
+        let live: HashSet<PartitionKey> = self
+            .list_partition()
+            .await?
+            .into_iter()
+            .map(|p| p.pkey)
+            .collect();
+        let deleted: Vec<String> = pkeys
+            .keys()
+            .filter(|k| !live.contains(k))
+            .map(|k| k.to_string())
+            .collect();
+        if !deleted.is_empty() {
+            return Err(UFMError::NotFound(format!(
+                "partitions {} deleted after the drain",
+                deleted.join(", ")
+            )));
+        }
+
+        let mut tx = self.transaction();
+        for (pkey, (ipoib, ports)) in pkeys {
+            let p = Partition {
+                name: "".to_string(),
+                pkey,
+                ipoib,
+                qos: PartitionQoS::default(),
+            };
+            tx = tx.bind(&p, ports);
+        }
+
+        match transaction_error("restore", &tx.commit().await) {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockUfm;
+    use crate::PortMembership;
+
+    const PKEYS: &str = r#"{
+        "0x10": {"partition": "tenant01", "ip_over_ib": true,
+                 "guids": [{"guid": "0000000000000011", "membership": "limited", "index0": false}]},
+        "0x7fff": {"partition": "management", "ip_over_ib": true,
+                   "guids": [{"guid": "0000000000000011", "membership": "full", "index0": true},
+                             {"guid": "0000000000000021", "membership": "full", "index0": true}]}
+    }"#;
+
+    fn membership(guid: &str, pkey: i32) -> GuidMembership {
+        GuidMembership {
+            guid: guid.to_string(),
+            pkey: PartitionKey::try_from(pkey).unwrap(),
+            name: "".to_string(),
+            ipoib: true,
+            membership: PortMembership::Full,
+            index0: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn drain_record_of_guid_without_partition() {
+        let mock = MockUfm::start().await;
+        mock.on("GET", "/resources/pkeys?guids_data=true", 200, PKEYS);
+
+        let record = mock
+            .ufm()
+            .drain_record(vec!["0000000000000031".to_string()])
+            .await
+            .unwrap();
+
+        assert!(record.memberships.is_empty());
+    }
+
+    #[tokio::test]
+    async fn drain_record_without_default_pkey() {
+        let mock = MockUfm::start().await;
+        mock.on("GET", "/resources/pkeys?guids_data=true", 200, PKEYS);
+
+        let record = mock
+            .ufm()
+            .drain_record(vec!["0x0000000000000011".to_string()])
+            .await
+            .unwrap();
+
+        assert_eq!(record.memberships.len(), 1);
+        assert_eq!(
+            record.memberships[0].pkey,
+            PartitionKey::try_from(0x10).unwrap()
+        );
+        assert_eq!(record.memberships[0].membership, PortMembership::Limited);
+        assert!(!record.memberships[0].index0);
+    }
+
+    #[tokio::test]
+    async fn drain_skips_default_pkey() {
+        let mock = MockUfm::start().await;
+        let record = DrainRecord {
+            guids: vec!["0000000000000011".to_string()],
+            memberships: vec![membership("0000000000000011", 0x7fff)],
+        };
+
+        mock.ufm().drain(&record).await.unwrap();
+        mock.ufm().restore(&record).await.unwrap();
+
+        assert!(mock.changes().is_empty());
+    }
+
+    #[tokio::test]
+    async fn restore_to_deleted_partition() {
+        let mock = MockUfm::start().await;
+        mock.on("GET", "/resources/pkeys?qos_conf=true", 200, "{}");
+        let record = DrainRecord {
+            guids: vec!["0000000000000011".to_string()],
+            memberships: vec![membership("0000000000000011", 0x10)],
+        };
+
+        let res = mock.ufm().restore(&record).await;
+
+        assert!(matches!(res, Err(UFMError::NotFound(m)) if m.contains("0x10")));
+        assert!(mock.changes().is_empty());
+    }
+}
@@ -0,0 +1,290 @@
+use serde::{Deserialize, Serialize};
+
+use crate::lookup::normalize_guid;
+use crate::{SystemType, Topology};
+
+/// The system in the graph of the fabric.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GraphNode {
+    /// The GUID of the system.
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub system_type: SystemType,
+    pub model: Option<String>,
+    pub rack: Option<String>,
+    pub tier: Option<i32>,
+}
+
+/// The cable in the graph of the fabric.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GraphEdge {
+    /// The GUID of the system on the source side.
+    pub source: String,
+    pub target: String,
+    pub source_port: i32,
+    pub target_port: i32,
+    pub speed: Option<String>,
+    pub width: Option<String>,
+    pub state: Option<String>,
+}
+
+impl GraphEdge {
+    /// The label of the edge, e.g. "HDR 4x Active".
+    pub fn label(&self) -> String {
+        [&self.speed, &self.width, &self.state]
+            .into_iter()
+            .flatten()
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// The graph of the fabric with the systems as nodes and the cables as edges; it is
+/// serialized as the node-link format of networkx.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FabricGraph {
+    pub directed: bool,
+    pub multigraph: bool,
+    pub nodes: Vec<GraphNode>,
+    pub links: Vec<GraphEdge>,
+}
+
+impl FabricGraph {
+    /// Export the graph as Graphviz DOT; the switches are boxes and the hosts are ellipses.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("graph fabric {\n");
+        for n in &self.nodes {
+            let shape = match n.system_type {
+                SystemType::Switch => "box",
+                _ => "ellipse",
+            };
+            dot.push_str(&format!(
+                "  \"{}\" [label=\"{}\", shape={}];\n",
+                dot_escape(&n.id),
+                dot_escape(&n.name),
+                shape
+            ));
+        }
+        for e in &self.links {
+            dot.push_str(&format!(
+                "  \"{}\" -- \"{}\" [label=\"{}\"];\n",
+                dot_escape(&e.source),
+                dot_escape(&e.target),
+                dot_escape(&e.label())
+            ));
+        }
+        dot.push_str("}\n");
+
+        dot
+    }
+
+    /// Export the graph as GraphML.
+    pub fn to_graphml(&self) -> String {
+        let mut xml = String::from(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+            "  <key id=\"name\" for=\"node\" attr.name=\"name\" attr.type=\"string\"/>\n",
+            "  <key id=\"type\" for=\"node\" attr.name=\"type\" attr.type=\"string\"/>\n",
+            "  <key id=\"model\" for=\"node\" attr.name=\"model\" attr.type=\"string\"/>\n",
+            "  <key id=\"rack\" for=\"node\" attr.name=\"rack\" attr.type=\"string\"/>\n",
+            "  <key id=\"tier\" for=\"node\" attr.name=\"tier\" attr.type=\"int\"/>\n",
+            "  <key id=\"source_port\" for=\"edge\" attr.name=\"source_port\" attr.type=\"int\"/>\n",
+            "  <key id=\"target_port\" for=\"edge\" attr.name=\"target_port\" attr.type=\"int\"/>\n",
+            "  <key id=\"speed\" for=\"edge\" attr.name=\"speed\" attr.type=\"string\"/>\n",
+            "  <key id=\"width\" for=\"edge\" attr.name=\"width\" attr.type=\"string\"/>\n",
+            "  <key id=\"state\" for=\"edge\" attr.name=\"state\" attr.type=\"string\"/>\n",
+            "  <key id=\"label\" for=\"edge\" attr.name=\"label\" attr.type=\"string\"/>\n",
+            "  <graph id=\"fabric\" edgedefault=\"undirected\">\n",
+        ));
+
+        let data =
+            |key: &str, v: &str| format!("      <data key=\"{}\">{}</data>\n", key, xml_escape(v));
+        for n in &self.nodes {
+            xml.push_str(&format!("    <node id=\"{}\">\n", xml_escape(&n.id)));
+            xml.push_str(&data("name", &n.name));
+            xml.push_str(&data("type", &n.system_type.to_string()));
+            if let Some(m) = &n.model {
+                xml.push_str(&data("model", m));
+            }
+            if let Some(r) = &n.rack {
+                xml.push_str(&data("rack", r));
+            }
+            if let Some(t) = &n.tier {
+                xml.push_str(&data("tier", &t.to_string()));
+            }
+            xml.push_str("    </node>\n");
+        }
+        for e in &self.links {
+            xml.push_str(&format!(
+                "    <edge source=\"{}\" target=\"{}\">\n",
+                xml_escape(&e.source),
+                xml_escape(&e.target)
+            ));
+            xml.push_str(&data("source_port", &e.source_port.to_string()));
+            xml.push_str(&data("target_port", &e.target_port.to_string()));
+            for (key, v) in [
+                ("speed", &e.speed),
+                ("width", &e.width),
+                ("state", &e.state),
+            ] {
+                if let Some(v) = v {
+                    xml.push_str(&data(key, v));
+                }
+            }
+            xml.push_str(&data("label", &e.label()));
+            xml.push_str("    </edge>\n");
+        }
+        xml.push_str("  </graph>\n</graphml>\n");
+
+        xml
+    }
+}
+
+fn dot_escape(v: &str) -> String {
+    v.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn xml_escape(v: &str) -> String {
+    v.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+impl Topology {
+    /// Build the graph of the topology; the nodes and edges are sorted to keep the output stable.
+    pub fn graph(&self) -> FabricGraph {
+        let mut nodes: Vec<GraphNode> = self
+            .systems()
+            .map(|s| GraphNode {
+                id: normalize_guid(&s.guid),
+                name: s.name.clone(),
+                system_type: s.system_type.clone(),
+                model: s.model.clone(),
+                rack: s.rack.clone(),
+                tier: self.tier(s),
+            })
+            .collect();
+        nodes.sort_by(|a, b| a.name.cmp(&b.name).then(a.id.cmp(&b.id)));
+
+        let mut links: Vec<GraphEdge> = self
+            .links()
+            .iter()
+            .filter_map(|l| match self.endpoints(l) {
+                (Some(src), Some(dst)) => Some(GraphEdge {
+                    source: normalize_guid(&src.guid),
+                    target: normalize_guid(&dst.guid),
+                    source_port: l.source_port_number,
+                    target_port: l.destination_port_number,
+                    speed: l.speed.clone(),
+                    width: l.width.clone(),
+                    state: l.state.as_ref().map(|s| s.to_string()),
+                }),
+                _ => None,
+            })
+            .collect();
+        links.sort_by(|a, b| {
+            (&a.source, a.source_port, &a.target).cmp(&(&b.source, b.source_port, &b.target))
+        });
+
+        FabricGraph {
+            directed: false,
+            multigraph: true,
+            nodes,
+            links,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Link, System};
+
+    fn system(guid: &str, name: &str, system_type: &str) -> System {
+        serde_json::from_value(serde_json::json!({
+            "system_guid": guid,
+            "system_name": name,
+            "type": system_type,
+            "model": "QM8700",
+        }))
+        .unwrap()
+    }
+
+    /// host "a" & <b> - leaf01, with an HDR link.
+    fn graph() -> FabricGraph {
+        let link: Link = serde_json::from_value(serde_json::json!({
+            "source_guid": "0x000000000000000A",
+            "source_port": 3,
+            "destination_guid": "0000000000000001",
+            "destination_port": 1,
+            "speed": "HDR",
+            "width": "4x",
+        }))
+        .unwrap();
+
+        Topology::new(
+            vec![
+                system("0000000000000001", "host \"a\" & <b>", "computer"),
+                system("000000000000000a", "leaf01", "switch"),
+            ],
+            vec![],
+            vec![link],
+        )
+        .graph()
+    }
+
+    #[test]
+    fn export_dot() {
+        assert_eq!(
+            graph().to_dot(),
+            concat!(
+                "graph fabric {\n",
+                "  \"0000000000000001\" [label=\"host \\\"a\\\" & <b>\", shape=ellipse];\n",
+                "  \"000000000000000a\" [label=\"leaf01\", shape=box];\n",
+                "  \"000000000000000a\" -- \"0000000000000001\" [label=\"HDR 4x\"];\n",
+                "}\n",
+            )
+        );
+    }
+
+    #[test]
+    fn export_graphml() {
+        let xml = graph().to_graphml();
+
+        assert!(xml.contains(concat!(
+            "    <node id=\"0000000000000001\">\n",
+            "      <data key=\"name\">host &quot;a&quot; &amp; &lt;b&gt;</data>\n",
+            "      <data key=\"type\">host</data>\n",
+            "      <data key=\"model\">QM8700</data>\n",
+            "    </node>\n",
+        )));
+        assert!(xml.contains(concat!(
+            "    <edge source=\"000000000000000a\" target=\"0000000000000001\">\n",
+            "      <data key=\"source_port\">3</data>\n",
+            "      <data key=\"target_port\">1</data>\n",
+            "      <data key=\"speed\">HDR</data>\n",
+            "      <data key=\"width\">4x</data>\n",
+            "      <data key=\"label\">HDR 4x</data>\n",
+            "    </edge>\n",
+        )));
+        assert!(xml.ends_with("  </graph>\n</graphml>\n"));
+    }
+
+    #[test]
+    fn export_networkx() {
+        let data = serde_json::to_value(graph()).unwrap();
+
+        assert_eq!(data["directed"], false);
+        assert_eq!(data["multigraph"], true);
+        assert_eq!(data["nodes"][0]["id"], "0000000000000001");
+        assert_eq!(data["nodes"][0]["name"], "host \"a\" & <b>");
+        assert_eq!(data["nodes"][1]["type"], "switch");
+        assert_eq!(data["links"][0]["source"], "000000000000000a");
+        assert_eq!(data["links"][0]["target"], "0000000000000001");
+        assert_eq!(data["links"][0]["source_port"], 3);
+    }
+}
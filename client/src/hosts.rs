@@ -0,0 +1,289 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::port::PhysicalPort;
+use crate::{
+    members_by_guid, ChangeSet, MembershipChange, Partition, PartitionKey, PortConfig,
+    PortMembership, UFMError, Ufm,
+};
+
+/// Map the hostnames to the GUIDs of their ports by the `system_name` of the ports.
+fn host_guids(
+    ports: Vec<PhysicalPort>,
+    hosts: &[String],
+) -> Result<HashMap<String, Vec<String>>, UFMError> {
+    let mut res: HashMap<String, Vec<String>> = HashMap::new();
+    for p in ports {
+        if hosts.contains(&p.system_name) {
+            res.entry(p.system_name).or_default().push(p.guid);
+        }
+    }
+
+    for h in hosts {
+        if !res.contains_key(h) {
+            return Err(UFMError::NotFound(h.to_string()));
+        }
+    }
+
+    Ok(res)
+}
+
+/// The changes between the members of a partition before and after a mutation, e.g.
+/// by the hosts-based pkey endpoint which does not report the GUIDs it changes.
+fn changes_between(before: Vec<PortConfig>, after: Vec<PortConfig>) -> ChangeSet {
+    let mut before = members_by_guid(before);
+    let mut changes = ChangeSet::default();
+    for (guid, a) in members_by_guid(after) {
+        match before.remove(&guid) {
+            None => changes.added.push(a),
+            Some(b) if b.membership != a.membership || b.index0 != a.index0 => {
+                changes.membership_changed.push(MembershipChange {
+                    guid: a.guid,
+                    from: b.membership,
+                    to: a.membership,
+                    from_index0: b.index0,
+                    to_index0: a.index0,
+                })
+            }
+            Some(_) => {}
+        }
+    }
+    changes.removed = before.into_values().collect();
+    changes.added.sort_by(|a, b| a.guid.cmp(&b.guid));
+    changes.removed.sort_by(|a, b| a.guid.cmp(&b.guid));
+
+    changes
+}
+
+impl Ufm {
+    /// Resolve the hostnames to their port GUIDs by the `system_name` of the Computer ports.
+    pub async fn resolve_hosts(
+        &self,
+        hosts: &[String],
+    ) -> Result<HashMap<String, Vec<String>>, UFMError> {
+        let path = String::from("/resources/ports?sys_type=Computer");
+        let ports: Vec<PhysicalPort> = self.client.list(&path).await?;
+
+        host_guids(ports, hosts)
+    }
+
+    /// List the members of the partition, or none if it does not exist.
+    async fn members_or_empty(&self, pkey: PartitionKey) -> Result<Vec<PortConfig>, UFMError> {
+        match self.list_partition_members(pkey).await {
+            Err(UFMError::NotFound(_)) => Ok(vec![]),
+            res => res,
+        }
+    }
+
+    /// Add all the ports of the hosts to the partition; the GUIDs of the hosts are resolved
+    /// by the client if the hosts-based pkey endpoint is not available in UFM. The changes
+    /// of the endpoint are read back from UFM, so they are empty in dry-run mode.
+    pub async fn bind_hosts(
+        &self,
+        p: Partition,
+        hosts: Vec<String>,
+        membership: PortMembership,
+        index0: bool,
+    ) -> Result<ChangeSet, UFMError> {
+        let path = String::from("/resources/pkeys/hosts");
+
+        #[derive(Serialize, Deserialize, Debug)]
+        struct Pkey {
+            pkey: String,
+            ip_over_ib: bool,
+            membership: PortMembership,
+            index0: bool,
+            hosts_names: String,
+        }
+
+        let pkey = Pkey {
+            pkey: p.pkey.to_string(),
+            ip_over_ib: p.ipoib,
+            membership: membership.clone(),
+            index0,
+            hosts_names: hosts.join(","),
+        };
+
+        let data = serde_json::to_string(&pkey)
+            .map_err(|_| UFMError::InvalidConfig("invalid partition".to_string()))?;
+
+        let before = self.members_or_empty(p.pkey).await?;
+        match self.client.post(&path, data).await {
+            Err(e) if e.is_not_found() => {
+                log::debug!("hosts-based pkey endpoint is not available, resolve hosts by ports");
+            }
+            res => {
+                res?;
+                return Ok(changes_between(
+                    before,
+                    self.members_or_empty(p.pkey).await?,
+                ));
+            }
+        };
+
+        let mut ports = vec![];
+        for guids in self.resolve_hosts(&hosts).await?.into_values() {
+            for guid in guids {
+                ports.push(PortConfig {
+                    guid,
+                    index0,
+                    membership: membership.clone(),
+                });
+            }
+        }
+
+        self.bind_ports(p, ports).await
+    }
+
+    /// Remove all the ports of the hosts from the partition; see `bind_hosts` for the changes.
+    pub async fn unbind_hosts(
+        &self,
+        pkey: PartitionKey,
+        hosts: Vec<String>,
+        force: bool,
+    ) -> Result<ChangeSet, UFMError> {
+        if pkey.is_default_pkey() && !force {
+            return Err(UFMError::ProtectedPKey(pkey.to_string()));
+        }
+
+        let path = String::from("/actions/remove_hosts_from_pkey");
+
+        #[derive(Serialize, Deserialize, Debug)]
+        struct Pkey {
+            pkey: String,
+            hosts_names: String,
+        }
+
+        let data = serde_json::to_string(&Pkey {
+            pkey: pkey.to_string(),
+            hosts_names: hosts.join(","),
+        })
+        .map_err(|_| UFMError::InvalidConfig("invalid partition".to_string()))?;
+
+        let before = self.members_or_empty(pkey).await?;
+        match self.client.post(&path, data).await {
+            Err(e) if e.is_not_found() => {
+                log::debug!("hosts-based pkey endpoint is not available, resolve hosts by ports");
+            }
+            res => {
+                res?;
+                return Ok(changes_between(before, self.members_or_empty(pkey).await?));
+            }
+        };
+
+        let guids = self
+            .resolve_hosts(&hosts)
+            .await?
+            .into_values()
+            .flatten()
+            .collect();
+
+        self.unbind_ports(pkey, guids, force).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockUfm;
+    use crate::PartitionQoS;
+
+    const PORTS: &str = r#"[
+        {"guid": "0000000000000011", "name": "0000000000000010_1", "systemID": "0000000000000010",
+         "lid": 1, "system_name": "node01", "logical_state": "Active"},
+        {"guid": "0000000000000012", "name": "0000000000000010_2", "systemID": "0000000000000010",
+         "lid": 2, "system_name": "node01", "logical_state": "Active"},
+        {"guid": "0000000000000021", "name": "0000000000000020_1", "systemID": "0000000000000020",
+         "lid": 3, "system_name": "node02", "logical_state": "Active"}
+    ]"#;
+
+    fn partition() -> Partition {
+        Partition {
+            name: "".to_string(),
+            pkey: PartitionKey::try_from(0x10).unwrap(),
+            ipoib: false,
+            qos: PartitionQoS::default(),
+        }
+    }
+
+    fn hosts(names: &[&str]) -> Vec<String> {
+        names.iter().map(|h| h.to_string()).collect()
+    }
+
+    #[test]
+    fn resolve_host_guids() {
+        let ports = serde_json::from_str(PORTS).unwrap();
+        let res = host_guids(ports, &hosts(&["node01", "node02"])).unwrap();
+
+        assert_eq!(res["node01"], vec!["0000000000000011", "0000000000000012"]);
+        assert_eq!(res["node02"], vec!["0000000000000021"]);
+    }
+
+    #[test]
+    fn resolve_unknown_host() {
+        let ports = serde_json::from_str(PORTS).unwrap();
+        let res = host_guids(ports, &hosts(&["node01", "node03"]));
+
+        assert!(matches!(res, Err(UFMError::NotFound(h)) if h == "node03"));
+    }
+
+    #[tokio::test]
+    async fn bind_hosts_by_endpoint() {
+        let mock = MockUfm::start().await;
+        mock.on("GET", "/resources/pkeys/0x10?guids_data=true", 200, "{}")
+            .on(
+                "GET",
+                "/resources/pkeys/0x10?guids_data=true",
+                200,
+                r#"{"guids": [{"guid": "0000000000000021", "membership": "full", "index0": true}]}"#,
+            )
+            .on("POST", "/resources/pkeys/hosts", 200, "");
+
+        let changes = mock
+            .ufm()
+            .bind_hosts(partition(), hosts(&["node02"]), PortMembership::Full, true)
+            .await
+            .unwrap();
+
+        assert_eq!(changes.added.len(), 1);
+        assert_eq!(changes.added[0].guid, "0000000000000021");
+        assert_eq!(mock.changes().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn bind_hosts_by_ports() {
+        let mock = MockUfm::start().await;
+        mock.on("GET", "/resources/ports?sys_type=Computer", 200, PORTS)
+            .on("POST", "/resources/pkeys", 200, "");
+
+        let changes = mock
+            .ufm()
+            .bind_hosts(partition(), hosts(&["node01"]), PortMembership::Full, true)
+            .await
+            .unwrap();
+
+        let guids: Vec<&str> = changes.added.iter().map(|p| p.guid.as_str()).collect();
+        assert_eq!(guids, vec!["0000000000000011", "0000000000000012"]);
+
+        let posts = mock.changes();
+        assert_eq!(posts.len(), 2);
+        assert_eq!(posts[0].path, "/resources/pkeys/hosts");
+        assert_eq!(posts[1].path, "/resources/pkeys");
+        assert!(posts[1].body.contains("0000000000000012"));
+    }
+
+    #[tokio::test]
+    async fn unbind_unknown_host_by_ports() {
+        let mock = MockUfm::start().await;
+        mock.on("GET", "/resources/ports?sys_type=Computer", 200, PORTS);
+
+        let res = mock
+            .ufm()
+            .unbind_hosts(partition().pkey, hosts(&["node03"]), false)
+            .await;
+
+        assert!(matches!(res, Err(UFMError::NotFound(h)) if h == "node03"));
+        assert_eq!(mock.changes().len(), 1);
+    }
+}
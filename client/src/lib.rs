@@ -1,55 +1,127 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::str::FromStr;
 
-use serde::{Deserialize, Serialize};
+use base64::Engine;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use thiserror::Error;
 use url::Url;
 
-use self::port::{PhysicalPort, Port, VirtualPort};
+use self::lookup::normalize_guid;
 use self::rest::{RestClient, RestClientConfig, RestError, RestScheme};
 
+pub use self::allocator::PartitionKeyRange;
+pub use self::analysis::{Reachability, ReachabilityMatrix};
+pub use self::cable::Cable;
+pub use self::counters::PortCounters;
+pub use self::drain::DrainRecord;
+pub use self::graph::{FabricGraph, GraphEdge, GraphNode};
+pub use self::lookup::{is_guid, GuidMembership, MembershipIndex};
+pub use self::port::{
+    LogicalState, PhysicalPort, PhysicalState, Port, PortFilter, PortType, VirtualPort,
+};
+pub use self::port_action::{Job, PortAction, PortActionJob};
+pub use self::profile::{check_qos_compliance, QoSDeviation, QoSProfiles};
+pub use self::qos::{Mtu, PartitionQoS, PartitionQoSBuilder, RateLimit, ServiceLevel};
+pub use self::reconciler::{
+    MemberSpec, PartitionSet, PartitionSpec, PartitionState, Plan, PlanAction, PlanOptions,
+};
+pub use self::rest::DryRunRecord;
+pub use self::snapshot::{ImportMode, PartitionSnapshot, SNAPSHOT_VERSION};
+pub use self::system::{System, SystemFilter, SystemType};
+pub use self::topology::{Link, Topology, TopologyFilter};
+pub use self::transaction::{
+    AppliedStep, FailedStep, Transaction, TransactionReport, TransactionStep,
+};
+pub use self::validator::{validate_fat_tree, FabricDesign, ValidationIssue, ValidationReport};
+
+mod allocator;
+mod analysis;
+mod cable;
+mod counters;
+mod drain;
+mod graph;
+mod hosts;
+mod lookup;
+#[cfg(test)]
+mod mock;
+mod orphan;
 mod port;
+mod port_action;
+mod profile;
+mod qos;
+mod reconciler;
 mod rest;
+mod snapshot;
+mod system;
+mod topology;
+mod transaction;
+mod validator;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct PartitionQoS {
-    // Default 2k; one of 2k or 4k; the MTU of the services.
-    pub mtu_limit: u16,
-    // Default is None, value can be range from 0-15
-    pub service_level: u8,
-    // Default is None, can be one of the following: 2.5, 10, 30, 5, 20, 40, 60, 80, 120, 14, 56, 112, 168, 25, 100, 200, or 300
-    pub rate_limit: f64,
-}
-
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, Hash)]
 #[serde(rename_all = "lowercase")]
 pub enum PortMembership {
     Limited,
+    #[default]
     Full,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct PortConfig {
     /// The GUID of Port.
     pub guid: String,
     /// Default false; store the PKey at index 0 of the PKey table of the GUID.
+    #[serde(default)]
     pub index0: bool,
     /// Default is full:
     ///   "full"    - members with full membership can communicate with all hosts (members) within the network/partition
     ///   "limited" - members with limited membership cannot communicate with other members with limited membership.
     ///               However, communication is allowed between every other combination of membership types.
+    #[serde(default)]
     pub membership: PortMembership,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+/// The 15-bit key of a partition; the membership bit (0x8000) of the
+/// 16-bit pkey is carried separately by `PortMembership`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct PartitionKey(i32);
 
+const PKEY_MASK: i32 = 0x7fff;
+const PKEY_MEMBERSHIP_BIT: i32 = 0x8000;
+
+/// The max number of GUIDs in one query of ports.
+const PORT_GUID_BATCH: usize = 100;
+
+/// The default pkey (0x7fff) which all the ports are member of.
+pub const DEFAULT_PKEY: PartitionKey = PartitionKey(PKEY_MASK);
+
 impl PartitionKey {
     pub fn is_default_pkey(&self) -> bool {
-        self.0 == 0x7fff
+        self.0 == PKEY_MASK
+    }
+
+    /// Build the PartitionKey and membership from the 16-bit pkey, e.g. 0x8005
+    /// is the pkey 0x5 with full membership.
+    pub fn from_raw(raw: u16) -> Result<(PartitionKey, PortMembership), UFMError> {
+        let raw = raw as i32;
+        let membership = match raw & PKEY_MEMBERSHIP_BIT {
+            0 => PortMembership::Limited,
+            _ => PortMembership::Full,
+        };
+
+        Ok((PartitionKey::try_from(raw & PKEY_MASK)?, membership))
+    }
+
+    /// Get the 16-bit pkey with the membership bit of the given membership.
+    pub fn to_raw(&self, membership: &PortMembership) -> u16 {
+        match membership {
+            PortMembership::Full => (self.0 | PKEY_MEMBERSHIP_BIT) as u16,
+            PortMembership::Limited => self.0 as u16,
+        }
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Partition {
     /// The name of Partition.
     pub name: String,
@@ -61,6 +133,42 @@ pub struct Partition {
     pub qos: PartitionQoS,
 }
 
+/// The membership or index0 change of a port in a partition.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MembershipChange {
+    pub guid: String,
+    pub from: PortMembership,
+    pub to: PortMembership,
+    pub from_index0: bool,
+    pub to_index0: bool,
+}
+
+/// The changes made by a partition mutation.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ChangeSet {
+    /// The ports which were added to the partition.
+    pub added: Vec<PortConfig>,
+    /// The ports which were removed from the partition.
+    pub removed: Vec<PortConfig>,
+    /// The ports whose membership (or index0) was changed.
+    pub membership_changed: Vec<MembershipChange>,
+    /// The GUIDs which were skipped as nothing changed.
+    pub unchanged: Vec<String>,
+}
+
+impl ChangeSet {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.membership_changed.is_empty()
+    }
+}
+
+fn members_by_guid(members: Vec<PortConfig>) -> HashMap<String, PortConfig> {
+    members
+        .into_iter()
+        .map(|m| (normalize_guid(&m.guid), m))
+        .collect()
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 struct Pkey {
     pkey: String,
@@ -74,25 +182,39 @@ impl TryFrom<i32> for PartitionKey {
     type Error = UFMError;
 
     fn try_from(pkey: i32) -> Result<Self, Self::Error> {
-        if pkey != (pkey & 0x7fff) {
-            return Err(UFMError::InvalidPKey(pkey.to_string()));
+        // The pkey 0x0000 is reserved, and the membership bit is not part of the key.
+        if pkey <= 0 || pkey != (pkey & PKEY_MASK) {
+            return Err(UFMError::InvalidPKey(format!("0x{:x}", pkey)));
         }
 
         Ok(PartitionKey(pkey))
     }
 }
 
+impl FromStr for PartitionKey {
+    type Err = UFMError;
+
+    fn from_str(pkey: &str) -> Result<Self, Self::Err> {
+        let p = pkey.trim();
+        let p = p
+            .strip_prefix("0x")
+            .or_else(|| p.strip_prefix("0X"))
+            .unwrap_or(p);
+        if p.is_empty() || !p.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(UFMError::InvalidPKey(pkey.to_string()));
+        }
+
+        let k = i32::from_str_radix(p, 16).map_err(|_| UFMError::InvalidPKey(pkey.to_string()))?;
+
+        PartitionKey::try_from(k).map_err(|_| UFMError::InvalidPKey(pkey.to_string()))
+    }
+}
+
 impl TryFrom<String> for PartitionKey {
     type Error = UFMError;
 
     fn try_from(pkey: String) -> Result<Self, Self::Error> {
-        let p = pkey.trim_start_matches("0x");
-        let k = i32::from_str_radix(p, 16);
-
-        match k {
-            Ok(v) => Ok(PartitionKey(v)),
-            Err(_e) => Err(UFMError::InvalidPKey(pkey.to_string())),
-        }
+        PartitionKey::from_str(&pkey)
     }
 }
 
@@ -100,7 +222,7 @@ impl TryFrom<&String> for PartitionKey {
     type Error = UFMError;
 
     fn try_from(pkey: &String) -> Result<Self, Self::Error> {
-        PartitionKey::try_from(pkey.to_string())
+        PartitionKey::from_str(pkey)
     }
 }
 
@@ -108,13 +230,13 @@ impl TryFrom<&str> for PartitionKey {
     type Error = UFMError;
 
     fn try_from(pkey: &str) -> Result<Self, Self::Error> {
-        PartitionKey::try_from(pkey.to_string())
+        PartitionKey::from_str(pkey)
     }
 }
 
-impl ToString for PartitionKey {
-    fn to_string(&self) -> String {
-        format!("0x{:x}", self.0)
+impl fmt::Display for PartitionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(&format!("0x{:x}", self.0))
     }
 }
 
@@ -124,6 +246,31 @@ impl From<PartitionKey> for i32 {
     }
 }
 
+impl Serialize for PartitionKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for PartitionKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // Accept both the number (e.g. 256) and the hex string (e.g. "0x100").
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum RawPKey {
+            Number(i32),
+            Text(String),
+        }
+
+        let pkey = match RawPKey::deserialize(deserializer)? {
+            RawPKey::Number(n) => PartitionKey::try_from(n),
+            RawPKey::Text(s) => PartitionKey::from_str(&s),
+        };
+
+        pkey.map_err(serde::de::Error::custom)
+    }
+}
+
 impl TryFrom<String> for PortMembership {
     type Error = UFMError;
 
@@ -144,6 +291,23 @@ impl TryFrom<&str> for PortMembership {
     }
 }
 
+impl FromStr for PortMembership {
+    type Err = UFMError;
+
+    fn from_str(membership: &str) -> Result<Self, Self::Err> {
+        PortMembership::try_from(membership)
+    }
+}
+
+impl fmt::Display for PortMembership {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PortMembership::Limited => f.pad("limited"),
+            PortMembership::Full => f.pad("full"),
+        }
+    }
+}
+
 pub struct Ufm {
     client: RestClient,
 }
@@ -158,6 +322,10 @@ pub enum UFMError {
     InvalidPKey(String),
     #[error("invalid configuration '{0}'")]
     InvalidConfig(String),
+    #[error("pkey '{0}' is protected, use force to change it")]
+    ProtectedPKey(String),
+    #[error("ports '{0}' are switch-to-switch links, use force to change them")]
+    ProtectedPort(String),
 }
 
 impl From<RestError> for UFMError {
@@ -176,6 +344,9 @@ pub struct UFMConfig {
     pub username: Option<String>,
     pub password: Option<String>,
     pub token: Option<String>,
+    /// Do not send the requests which change UFM, e.g. POST/PUT/DELETE, but record them;
+    /// the records are taken by `Ufm::take_dry_run_records`.
+    pub dry_run: bool,
 }
 
 pub fn connect(conf: UFMConfig) -> Result<Ufm, UFMError> {
@@ -198,7 +369,8 @@ pub fn connect(conf: UFMConfig) -> Result<Ufm, UFMError> {
 
             (
                 "/ufmRest".to_string(),
-                base64::encode(format!("{}:{}", username, password)),
+                base64::engine::general_purpose::STANDARD
+                    .encode(format!("{}:{}", username, password)),
             )
         }
         Some(t) => ("/ufmRestV3".to_string(), t.to_string()),
@@ -210,31 +382,161 @@ pub fn connect(conf: UFMConfig) -> Result<Ufm, UFMError> {
         auth_info,
         base_path,
         scheme: RestScheme::from(addr.scheme().to_string()),
+        dry_run: conf.dry_run,
     })?;
 
     Ok(Ufm { client: c })
 }
 
 impl Ufm {
-    pub async fn bind_ports(&self, p: Partition, ports: Vec<PortConfig>) -> Result<(), UFMError> {
+    pub fn is_dry_run(&self) -> bool {
+        self.client.is_dry_run()
+    }
+
+    /// Take the requests which would have been sent to UFM in dry-run mode.
+    pub fn take_dry_run_records(&self) -> Vec<DryRunRecord> {
+        self.client.take_dry_run_records()
+    }
+
+    /// Add the ports to the partition or change their membership; the ports which are
+    /// already member of the partition with the same membership are skipped.
+    pub async fn bind_ports(
+        &self,
+        p: Partition,
+        ports: Vec<PortConfig>,
+    ) -> Result<ChangeSet, UFMError> {
         let path = String::from("/resources/pkeys");
 
-        let mut membership = PortMembership::Full;
-        let mut index0 = true;
+        let current = match self.list_partition_members(p.pkey).await {
+            Ok(members) => Some(members_by_guid(members)),
+            Err(UFMError::NotFound(_)) => None,
+            Err(e) => return Err(e),
+        };
 
-        let mut guids = vec![];
+        let mut changes = ChangeSet::default();
+        // UFM takes one membership/index0 for all the GUIDs of a request.
+        let mut groups: BTreeMap<(String, bool), Vec<PortConfig>> = BTreeMap::new();
         for pb in ports {
-            membership = pb.membership.clone();
-            index0 = pb.index0;
-            guids.push(pb.guid.to_string());
+            match current
+                .as_ref()
+                .and_then(|c| c.get(&normalize_guid(&pb.guid)))
+            {
+                None => changes.added.push(pb.clone()),
+                Some(c) if c.membership != pb.membership || c.index0 != pb.index0 => {
+                    changes.membership_changed.push(MembershipChange {
+                        guid: pb.guid.clone(),
+                        from: c.membership.clone(),
+                        to: pb.membership.clone(),
+                        from_index0: c.index0,
+                        to_index0: pb.index0,
+                    })
+                }
+                Some(_) => {
+                    changes.unchanged.push(pb.guid.clone());
+                    continue;
+                }
+            };
+
+            groups
+                .entry((pb.membership.to_string(), pb.index0))
+                .or_default()
+                .push(pb);
+        }
+
+        // Create the partition without ports if it does not exist.
+        if current.is_none() && groups.is_empty() {
+            groups.insert((PortMembership::Full.to_string(), true), vec![]);
+        }
+
+        for ((membership, index0), ports) in groups {
+            let pkey = Pkey {
+                pkey: p.pkey.to_string(),
+                ip_over_ib: p.ipoib,
+                membership: PortMembership::try_from(membership)?,
+                index0,
+                guids: ports.into_iter().map(|pb| pb.guid).collect(),
+            };
+
+            let data = serde_json::to_string(&pkey)
+                .map_err(|_| UFMError::InvalidConfig("invalid partition".to_string()))?;
+
+            self.client.post(&path, data).await?;
+        }
+
+        Ok(changes)
+    }
+
+    /// Remove the ports from the partition; the ports which are not member of the
+    /// partition are skipped.
+    pub async fn unbind_ports(
+        &self,
+        pkey: PartitionKey,
+        guids: Vec<String>,
+        force: bool,
+    ) -> Result<ChangeSet, UFMError> {
+        if pkey.is_default_pkey() && !force {
+            return Err(UFMError::ProtectedPKey(pkey.to_string()));
+        }
+
+        let path = String::from("/actions/remove_guids_from_pkey");
+
+        let current = members_by_guid(self.list_partition_members(pkey).await?);
+
+        let mut changes = ChangeSet::default();
+        for guid in guids {
+            match current.get(&normalize_guid(&guid)) {
+                Some(c) => changes.removed.push(c.clone()),
+                None => changes.unchanged.push(guid),
+            }
+        }
+
+        if changes.removed.is_empty() {
+            return Ok(changes);
+        }
+
+        #[derive(Serialize, Deserialize, Debug)]
+        struct Pkey {
+            pkey: String,
+            guids: Vec<String>,
         }
 
         let pkey = Pkey {
-            pkey: p.pkey.clone().to_string(),
+            pkey: pkey.to_string(),
+            guids: changes.removed.iter().map(|c| c.guid.clone()).collect(),
+        };
+
+        let data = serde_json::to_string(&pkey)
+            .map_err(|_| UFMError::InvalidConfig("invalid partition".to_string()))?;
+
+        self.client.post(&path, data).await?;
+
+        Ok(changes)
+    }
+
+    pub async fn create_partition(&self, p: &Partition) -> Result<(), UFMError> {
+        p.qos.validate()?;
+        let path = String::from("/resources/pkeys/add");
+
+        #[derive(Serialize, Deserialize, Debug)]
+        struct Pkey {
+            pkey: String,
+            #[serde(skip_serializing_if = "String::is_empty")]
+            partition_name: String,
+            index0: bool,
+            ip_over_ib: bool,
+            mtu_limit: Mtu,
+            service_level: ServiceLevel,
+            rate_limit: RateLimit,
+        }
+
+        let pkey = Pkey {
+            pkey: p.pkey.to_string(),
+            partition_name: p.name.clone(),
+            index0: true,
             ip_over_ib: p.ipoib,
-            membership,
-            index0,
-            guids,
+            mtu_limit: p.qos.mtu_limit,
+            service_level: p.qos.service_level,
+            rate_limit: p.qos.rate_limit,
         };
 
         let data = serde_json::to_string(&pkey)
@@ -245,22 +547,27 @@ impl Ufm {
         Ok(())
     }
 
-    pub async fn unbind_ports(
+    pub async fn update_partition_qos(
         &self,
         pkey: PartitionKey,
-        guids: Vec<String>,
+        qos: &PartitionQoS,
     ) -> Result<(), UFMError> {
-        let path = String::from("/actions/remove_guids_from_pkey");
+        qos.validate()?;
+        let path = String::from("/resources/pkeys/qos_conf");
 
         #[derive(Serialize, Deserialize, Debug)]
         struct Pkey {
             pkey: String,
-            guids: Vec<String>,
+            mtu_limit: Mtu,
+            service_level: ServiceLevel,
+            rate_limit: RateLimit,
         }
 
         let pkey = Pkey {
-            pkey: pkey.clone().to_string(),
-            guids,
+            pkey: pkey.to_string(),
+            mtu_limit: qos.mtu_limit,
+            service_level: qos.service_level,
+            rate_limit: qos.rate_limit,
         };
 
         let data = serde_json::to_string(&pkey)
@@ -274,7 +581,7 @@ impl Ufm {
     pub async fn get_partition(&self, pkey: &str) -> Result<Partition, UFMError> {
         let pkey = PartitionKey::try_from(pkey)?;
 
-        let path = format!("/resources/pkeys/{}?qos_conf=true", pkey.to_string());
+        let path = format!("/resources/pkeys/{}?qos_conf=true", pkey);
 
         #[derive(Serialize, Deserialize, Debug)]
         struct Pkey {
@@ -317,13 +624,35 @@ impl Ufm {
         Ok(parts)
     }
 
-    pub async fn delete_partition(&self, pkey: &str) -> Result<(), UFMError> {
+    pub async fn delete_partition(&self, pkey: &str, force: bool) -> Result<(), UFMError> {
+        let pkey = PartitionKey::try_from(pkey)?;
+        if pkey.is_default_pkey() && !force {
+            return Err(UFMError::ProtectedPKey(pkey.to_string()));
+        }
+
         let path = format!("/resources/pkeys/{}", pkey);
         self.client.delete(&path).await?;
 
         Ok(())
     }
 
+    /// List the members of the partition with their membership, from the `guids_data` of the pkey.
+    pub async fn list_partition_members(
+        &self,
+        pkey: PartitionKey,
+    ) -> Result<Vec<PortConfig>, UFMError> {
+        #[derive(Serialize, Deserialize, Debug)]
+        struct PkeyWithGUIDs {
+            #[serde(default)]
+            guids: Vec<PortConfig>,
+        }
+
+        let path = format!("/resources/pkeys/{}?guids_data=true", pkey);
+        let pk: PkeyWithGUIDs = self.client.get(&path).await?;
+
+        Ok(pk.guids)
+    }
+
     pub async fn list_port(&self, pkey: PartitionKey) -> Result<Vec<Port>, UFMError> {
         let mut res = Vec::new();
         // get GUIDs from pkey
@@ -334,12 +663,25 @@ impl Ufm {
             pub guids: Vec<PortConfig>,
         }
 
-        let path = format!("resources/pkeys/{}?guids_data=true", pkey.to_string());
+        let path = format!("resources/pkeys/{}?guids_data=true", pkey);
         let pkeywithguids: PkeyWithGUIDs = self.client.get(&path).await?;
 
-        // list physical ports
-        let path = String::from("/resources/ports?sys_type=Computer");
-        let physical_ports: Vec<PhysicalPort> = self.client.list(&path).await?;
+        // list physical ports; only the ports of the pkey are fetched except the default pkey
+        let mut filter = PortFilter {
+            sys_type: Some("Computer".to_string()),
+            ..PortFilter::default()
+        };
+        if !pkey.is_default_pkey() {
+            filter.guids = pkeywithguids
+                .guids
+                .iter()
+                .map(|pc| normalize_guid(&pc.guid))
+                .collect();
+        }
+        let physical_ports = match filter.guids.is_empty() && !pkey.is_default_pkey() {
+            true => vec![],
+            false => self.list_physical_ports(&filter).await?,
+        };
 
         // list virtual ports
         // let path = String::from("/resources/vports");
@@ -347,7 +689,7 @@ impl Ufm {
 
         let mut port_map = HashMap::new();
         for pport in physical_ports {
-            port_map.insert(pport.guid.clone(), Port::from(pport));
+            port_map.insert(normalize_guid(&pport.guid), Port::from(pport));
         }
         // for vport in virtual_ports {
         //     port_map.insert(vport.virtual_port_guid.clone(), Port::from(vport));
@@ -356,7 +698,7 @@ impl Ufm {
         if !pkey.is_default_pkey() {
             for port_config in pkeywithguids.guids {
                 let guid = port_config.guid;
-                match port_map.get(&guid) {
+                match port_map.get(&normalize_guid(&guid)) {
                     Some(p) => {
                         res.push(p.clone());
                     }
@@ -367,7 +709,7 @@ impl Ufm {
                             system_id: "".to_string(),
                             lid: 65535,
                             system_name: "".to_string(),
-                            logical_state: "Unknown".to_string(),
+                            logical_state: LogicalState::Unknown,
                             parent_guid: None,
                             port_type: None,
                         };
@@ -382,6 +724,64 @@ impl Ufm {
         Ok(res)
     }
 
+    /// List the physical ports with all the attributes reported by UFM.
+    pub async fn list_physical_ports(
+        &self,
+        filter: &PortFilter,
+    ) -> Result<Vec<PhysicalPort>, UFMError> {
+        let filter = PortFilter {
+            guids: filter.guids.iter().map(|g| normalize_guid(g)).collect(),
+            ..filter.clone()
+        };
+
+        // Query the GUIDs in batches to keep the URL short.
+        let batches = match filter.guids.is_empty() {
+            true => vec![filter.clone()],
+            false => filter
+                .guids
+                .chunks(PORT_GUID_BATCH)
+                .map(|guids| PortFilter {
+                    guids: guids.to_vec(),
+                    ..filter.clone()
+                })
+                .collect(),
+        };
+
+        let mut res = vec![];
+        for f in batches {
+            let path = format!("/resources/ports{}", f.to_query());
+            let ports: Vec<PhysicalPort> = self.client.list(&path).await?;
+
+            // The GUIDs are ignored by some versions of UFM, which return all the ports;
+            // filter them by all the GUIDs instead of downloading them for each batch.
+            let unfiltered = !f.guids.is_empty()
+                && ports
+                    .iter()
+                    .any(|p| !f.guids.contains(&normalize_guid(&p.guid)));
+            if unfiltered {
+                return Ok(ports.into_iter().filter(|p| filter.matches(p)).collect());
+            }
+
+            res.extend(ports.into_iter().filter(|p| f.matches(p)));
+        }
+
+        Ok(res)
+    }
+
+    /// Get the physical ports of the GUIDs; the GUIDs which are not found are skipped.
+    pub async fn get_ports(&self, guids: &[String]) -> Result<Vec<PhysicalPort>, UFMError> {
+        if guids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let filter = PortFilter {
+            guids: guids.to_vec(),
+            ..PortFilter::default()
+        };
+
+        self.list_physical_ports(&filter).await
+    }
+
     pub async fn version(&self) -> Result<String, UFMError> {
         #[derive(Serialize, Deserialize, Debug)]
         struct Version {
@@ -394,3 +794,91 @@ impl Ufm {
         Ok(v.ufm_release_version)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pkey(s: &str) -> Result<PartitionKey, UFMError> {
+        PartitionKey::from_str(s)
+    }
+
+    #[test]
+    fn parse_pkey() {
+        assert_eq!(pkey("0x7fff").unwrap(), DEFAULT_PKEY);
+        assert!(pkey("0x7fff").unwrap().is_default_pkey());
+        assert_eq!(i32::from(pkey("0X5").unwrap()), 5);
+        assert_eq!(i32::from(pkey("a").unwrap()), 0xa);
+        assert_eq!(i32::from(pkey("  0x100\n").unwrap()), 0x100);
+    }
+
+    #[test]
+    fn parse_invalid_pkey() {
+        for s in [
+            "0", "0x0", "0x8000", "0xffff", "0x10000", "", "0x", "0x 5", "-1", "0xg",
+        ] {
+            assert!(
+                matches!(pkey(s), Err(UFMError::InvalidPKey(_))),
+                "{:?} should be invalid",
+                s
+            );
+        }
+    }
+
+    #[test]
+    fn pkey_from_raw() {
+        let (k, m) = PartitionKey::from_raw(0x8005).unwrap();
+        assert_eq!(i32::from(k), 5);
+        assert_eq!(m, PortMembership::Full);
+
+        let (k, m) = PartitionKey::from_raw(0x0005).unwrap();
+        assert_eq!(i32::from(k), 5);
+        assert_eq!(m, PortMembership::Limited);
+
+        let (k, m) = PartitionKey::from_raw(0xffff).unwrap();
+        assert_eq!(k, DEFAULT_PKEY);
+        assert_eq!(m, PortMembership::Full);
+
+        // Only the membership bit is set, the key itself is the reserved 0x0000.
+        assert!(PartitionKey::from_raw(0x8000).is_err());
+        assert!(PartitionKey::from_raw(0).is_err());
+    }
+
+    #[test]
+    fn pkey_to_raw() {
+        let k = pkey("0x5").unwrap();
+        assert_eq!(k.to_raw(&PortMembership::Full), 0x8005);
+        assert_eq!(k.to_raw(&PortMembership::Limited), 0x0005);
+        assert_eq!(
+            PartitionKey::from_raw(k.to_raw(&PortMembership::Limited)).unwrap(),
+            (k, PortMembership::Limited)
+        );
+    }
+
+    #[test]
+    fn pkey_serde() {
+        let k: PartitionKey = serde_json::from_str("256").unwrap();
+        assert_eq!(k, pkey("0x100").unwrap());
+        let k: PartitionKey = serde_json::from_str("\"0x100\"").unwrap();
+        assert_eq!(serde_json::to_string(&k).unwrap(), "\"0x100\"");
+        assert!(serde_json::from_str::<PartitionKey>("32768").is_err());
+    }
+
+    #[tokio::test]
+    async fn reject_invalid_qos() {
+        let mock = crate::mock::MockUfm::start().await;
+        let qos: PartitionQoS =
+            serde_json::from_str(r#"{"mtu_limit": 2, "service_level": 0, "rate_limit": 400}"#)
+                .unwrap();
+        let p = Partition {
+            name: "tenant01".to_string(),
+            pkey: pkey("0x10").unwrap(),
+            ipoib: true,
+            qos: qos.clone(),
+        };
+
+        assert!(mock.ufm().create_partition(&p).await.is_err());
+        assert!(mock.ufm().update_partition_qos(p.pkey, &qos).await.is_err());
+        assert!(mock.changes().is_empty());
+    }
+}
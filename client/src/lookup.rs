@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{PartitionKey, PortConfig, PortMembership, UFMError, Ufm};
+
+/// The membership of a GUID in a partition.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GuidMembership {
+    pub guid: String,
+    pub pkey: PartitionKey,
+    pub name: String,
+    pub ipoib: bool,
+    pub membership: PortMembership,
+    pub index0: bool,
+}
+
+/// The index from GUID to the partitions which it is member of.
+#[derive(Debug, Default)]
+pub struct MembershipIndex {
+    guids: HashMap<String, Vec<GuidMembership>>,
+}
+
+impl MembershipIndex {
+    /// Get the partitions of the GUID, sorted by pkey.
+    pub fn get(&self, guid: &str) -> Vec<GuidMembership> {
+        self.guids
+            .get(&normalize_guid(guid))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Add the membership of the GUID, keeping its partitions sorted by pkey.
+    pub(crate) fn insert(&mut self, membership: GuidMembership) {
+        let ms = self.guids.entry(membership.guid.clone()).or_default();
+        ms.push(membership);
+        ms.sort_by_key(|m| m.pkey);
+    }
+
+    /// Consume the index and iterate all the memberships.
+    pub fn into_memberships(self) -> impl Iterator<Item = GuidMembership> {
+        self.guids.into_values().flatten()
+    }
+}
+
+/// Normalize the GUID to the format of UFM, e.g. "0x1070FD0300176625" to "1070fd0300176625".
+pub(crate) fn normalize_guid(guid: &str) -> String {
+    let guid = guid.trim();
+    guid.strip_prefix("0x")
+        .or_else(|| guid.strip_prefix("0X"))
+        .unwrap_or(guid)
+        .to_lowercase()
+}
+
+/// Check whether the target is a GUID, e.g. "0x1070FD0300176625", rather than a name.
+pub fn is_guid(target: &str) -> bool {
+    let guid = normalize_guid(target);
+    guid.len() == 16 && guid.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+impl Ufm {
+    /// Build the membership index of all the partitions in one call.
+    pub async fn membership_index(&self) -> Result<MembershipIndex, UFMError> {
+        #[derive(Serialize, Deserialize, Debug)]
+        struct PkeyWithGUIDs {
+            partition: String,
+            ip_over_ib: bool,
+            #[serde(default)]
+            guids: Vec<PortConfig>,
+        }
+
+        let path = String::from("/resources/pkeys?guids_data=true");
+        let pkeys: HashMap<String, PkeyWithGUIDs> = self.client.list(&path).await?;
+
+        let mut index = MembershipIndex::default();
+        for (k, v) in pkeys {
+            let pkey = PartitionKey::try_from(&k)?;
+            for pc in v.guids {
+                index.insert(GuidMembership {
+                    guid: normalize_guid(&pc.guid),
+                    pkey,
+                    name: v.partition.clone(),
+                    ipoib: v.ip_over_ib,
+                    membership: pc.membership,
+                    index0: pc.index0,
+                });
+            }
+        }
+
+        Ok(index)
+    }
+
+    /// List the partitions which the GUID is member of.
+    pub async fn partitions_for_guid(&self, guid: &str) -> Result<Vec<GuidMembership>, UFMError> {
+        Ok(self.membership_index().await?.get(guid))
+    }
+
+    /// List the partitions which the ports of the system (host) are member of.
+    pub async fn partitions_for_system(&self, name: &str) -> Result<Vec<GuidMembership>, UFMError> {
+        let guids = self
+            .resolve_hosts(&[name.to_string()])
+            .await?
+            .remove(name)
+            .unwrap_or_default();
+
+        let index = self.membership_index().await?;
+
+        Ok(guids.iter().flat_map(|g| index.get(g)).collect())
+    }
+}
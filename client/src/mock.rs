@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, Server, StatusCode};
+
+use crate::{UFMConfig, Ufm};
+
+/// The request received by the mock UFM.
+#[derive(Debug, Clone)]
+pub struct MockRequest {
+    pub method: String,
+    /// The path without the base path, with the query, e.g. "/resources/pkeys?qos_conf=true".
+    pub path: String,
+    pub body: String,
+}
+
+#[derive(Default)]
+struct State {
+    /// The responses of each route; the last one is kept for the later requests.
+    routes: HashMap<(String, String), Vec<(u16, String)>>,
+    requests: Vec<MockRequest>,
+}
+
+/// A UFM server on localhost which answers the requests with the given responses, and
+/// 404 for the unknown routes.
+pub struct MockUfm {
+    addr: SocketAddr,
+    state: Arc<Mutex<State>>,
+}
+
+impl MockUfm {
+    pub async fn start() -> MockUfm {
+        let state: Arc<Mutex<State>> = Arc::default();
+
+        let s = state.clone();
+        let make_svc = make_service_fn(move |_| {
+            let s = s.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |req| {
+                    let s = s.clone();
+                    async move { Ok::<_, Infallible>(handle(&s, req).await) }
+                }))
+            }
+        });
+
+        let server = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_svc);
+        let addr = server.local_addr();
+        tokio::spawn(server);
+
+        MockUfm { addr, state }
+    }
+
+    /// Answer the requests of the method and path, in order of the calls.
+    pub fn on(&self, method: &str, path: &str, status: u16, body: &str) -> &Self {
+        self.state
+            .lock()
+            .unwrap()
+            .routes
+            .entry((method.to_string(), path.to_string()))
+            .or_default()
+            .push((status, body.to_string()));
+        self
+    }
+
+    pub fn ufm(&self) -> Ufm {
+        crate::connect(UFMConfig {
+            address: format!("http://{}", self.addr),
+            username: None,
+            password: None,
+            token: Some("token".to_string()),
+            dry_run: false,
+        })
+        .unwrap()
+    }
+
+    /// The requests which change UFM, i.e. all but GET.
+    pub fn changes(&self) -> Vec<MockRequest> {
+        self.state
+            .lock()
+            .unwrap()
+            .requests
+            .iter()
+            .filter(|r| r.method != "GET")
+            .cloned()
+            .collect()
+    }
+}
+
+async fn handle(state: &Mutex<State>, req: Request<Body>) -> Response<Body> {
+    let method = req.method().to_string();
+    let path = req
+        .uri()
+        .path_and_query()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_default();
+    let path = path.strip_prefix("/ufmRestV3").unwrap_or(&path).to_string();
+    let body = hyper::body::to_bytes(req.into_body())
+        .await
+        .map(|b| String::from_utf8_lossy(&b).to_string())
+        .unwrap_or_default();
+
+    let mut state = state.lock().unwrap();
+    state.requests.push(MockRequest {
+        method: method.clone(),
+        path: path.clone(),
+        body,
+    });
+
+    let (status, body) = match state.routes.get_mut(&(method, path)) {
+        Some(responses) if responses.len() > 1 => responses.remove(0),
+        Some(responses) => responses[0].clone(),
+        None => (404, "not found".to_string()),
+    };
+
+    Response::builder()
+        .status(StatusCode::from_u16(status).unwrap())
+        .body(Body::from(body))
+        .unwrap()
+}
@@ -0,0 +1,93 @@
+use std::collections::HashSet;
+
+use crate::lookup::normalize_guid;
+use crate::port::{PhysicalPort, VirtualPort};
+use crate::{GuidMembership, UFMError, Ufm};
+
+impl Ufm {
+    /// List the partition members which are neither a physical nor a virtual port in
+    /// the fabric; the default pkey is skipped.
+    pub async fn find_orphaned_members(&self) -> Result<Vec<GuidMembership>, UFMError> {
+        let path = String::from("/resources/ports");
+        let physical_ports: Vec<PhysicalPort> = self.client.list(&path).await?;
+
+        // UFM without virtual ports has no vports resource.
+        let path = String::from("/resources/vports");
+        let virtual_ports: Vec<VirtualPort> = match self.client.list(&path).await {
+            Err(e) if e.is_not_found() => vec![],
+            res => res?,
+        };
+
+        let mut guids = HashSet::new();
+        for pport in physical_ports {
+            guids.insert(normalize_guid(&pport.guid));
+        }
+        for vport in virtual_ports {
+            guids.insert(normalize_guid(&vport.virtual_port_guid));
+        }
+
+        let mut orphans: Vec<GuidMembership> = self
+            .membership_index()
+            .await?
+            .into_memberships()
+            .filter(|m| !m.pkey.is_default_pkey() && !guids.contains(&m.guid))
+            .collect();
+        orphans.sort_by(|a, b| a.pkey.cmp(&b.pkey).then(a.guid.cmp(&b.guid)));
+
+        Ok(orphans)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mock::MockUfm;
+
+    const PORTS: &str = r#"[
+        {"guid": "0000000000000011", "name": "hpc-cloud01_1", "systemID": "0000000000000010",
+         "lid": 1, "system_name": "hpc-cloud01", "logical_state": "Active"}
+    ]"#;
+
+    const VPORTS: &str = r#"[
+        {"virtual_port_guid": "0000000000000012", "system_guid": "0000000000000010",
+         "virtual_port_lid": 2, "system_name": "hpc-cloud01", "virtual_port_state": "Active",
+         "port_guid": "0000000000000011"}
+    ]"#;
+
+    const PKEYS: &str = r#"{
+        "0x10": {"partition": "tenant01", "ip_over_ib": true,
+                 "guids": [{"guid": "0000000000000011", "membership": "full", "index0": true},
+                           {"guid": "0000000000000012", "membership": "full", "index0": false},
+                           {"guid": "0000000000000021", "membership": "limited", "index0": false}]},
+        "0x7fff": {"partition": "management", "ip_over_ib": true,
+                   "guids": [{"guid": "0000000000000031", "membership": "full", "index0": true}]}
+    }"#;
+
+    #[tokio::test]
+    async fn find_orphaned_members() {
+        let mock = MockUfm::start().await;
+        mock.on("GET", "/resources/ports", 200, PORTS)
+            .on("GET", "/resources/vports", 200, VPORTS)
+            .on("GET", "/resources/pkeys?guids_data=true", 200, PKEYS);
+
+        let orphans = mock.ufm().find_orphaned_members().await.unwrap();
+
+        let guids: Vec<&str> = orphans.iter().map(|m| m.guid.as_str()).collect();
+        assert_eq!(guids, vec!["0000000000000021"]);
+    }
+
+    #[tokio::test]
+    async fn find_orphaned_members_without_vports() {
+        let mock = MockUfm::start().await;
+        mock.on("GET", "/resources/ports", 200, PORTS).on(
+            "GET",
+            "/resources/pkeys?guids_data=true",
+            200,
+            PKEYS,
+        );
+
+        let orphans = mock.ufm().find_orphaned_members().await.unwrap();
+
+        let guids: Vec<&str> = orphans.iter().map(|m| m.guid.as_str()).collect();
+        assert_eq!(guids, vec!["0000000000000012", "0000000000000021"]);
+    }
+}
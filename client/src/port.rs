@@ -1,6 +1,118 @@
-use std::fmt::Display;
+use std::fmt::{self, Display};
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::lookup::normalize_guid;
+
+/// The logical state of a port; the unrecognized states reported by UFM are kept as is.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum LogicalState {
+    Down,
+    Init,
+    Armed,
+    Active,
+    Unknown,
+    Other(String),
+}
+
+impl From<&str> for LogicalState {
+    fn from(state: &str) -> Self {
+        match state.trim().to_lowercase().as_str() {
+            "down" => LogicalState::Down,
+            "init" | "initialize" => LogicalState::Init,
+            "armed" => LogicalState::Armed,
+            "active" => LogicalState::Active,
+            "" | "unknown" => LogicalState::Unknown,
+            _ => LogicalState::Other(state.to_string()),
+        }
+    }
+}
+
+impl Display for LogicalState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogicalState::Down => f.pad("Down"),
+            LogicalState::Init => f.pad("Init"),
+            LogicalState::Armed => f.pad("Armed"),
+            LogicalState::Active => f.pad("Active"),
+            LogicalState::Unknown => f.pad("Unknown"),
+            LogicalState::Other(s) => f.pad(s),
+        }
+    }
+}
+
+/// The physical state of a port; the unrecognized states reported by UFM are kept as is.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PhysicalState {
+    Sleep,
+    Polling,
+    Disabled,
+    Training,
+    LinkUp,
+    LinkErrorRecovery,
+    PhyTest,
+    Unknown,
+    Other(String),
+}
+
+impl From<&str> for PhysicalState {
+    fn from(state: &str) -> Self {
+        let s: String = state
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_lowercase();
+        match s.as_str() {
+            "sleep" => PhysicalState::Sleep,
+            "polling" => PhysicalState::Polling,
+            "disabled" => PhysicalState::Disabled,
+            "training" | "portconfigurationtraining" => PhysicalState::Training,
+            "linkup" => PhysicalState::LinkUp,
+            "linkerrorrecovery" => PhysicalState::LinkErrorRecovery,
+            "phytest" => PhysicalState::PhyTest,
+            "" | "unknown" => PhysicalState::Unknown,
+            _ => PhysicalState::Other(state.to_string()),
+        }
+    }
+}
+
+impl Display for PhysicalState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PhysicalState::Sleep => f.pad("Sleep"),
+            PhysicalState::Polling => f.pad("Polling"),
+            PhysicalState::Disabled => f.pad("Disabled"),
+            PhysicalState::Training => f.pad("Training"),
+            PhysicalState::LinkUp => f.pad("Link Up"),
+            PhysicalState::LinkErrorRecovery => f.pad("Link Error Recovery"),
+            PhysicalState::PhyTest => f.pad("Phy Test"),
+            PhysicalState::Unknown => f.pad("Unknown"),
+            PhysicalState::Other(s) => f.pad(s),
+        }
+    }
+}
+
+macro_rules! state_serde {
+    ($state:ty) => {
+        impl Serialize for $state {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $state {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let s = String::deserialize(deserializer)?;
+                Ok(<$state>::from(s.as_str()))
+            }
+        }
+    };
+}
+
+pub(crate) use state_serde;
+
+state_serde!(LogicalState);
+state_serde!(PhysicalState);
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "lowercase")]
@@ -17,13 +129,13 @@ pub struct Port {
     pub system_id: String,
     pub lid: i32,
     pub system_name: String,
-    pub logical_state: String,
+    pub logical_state: LogicalState,
     pub parent_guid: Option<String>,
     pub port_type: Option<PortType>,
 }
 
 impl Display for Port {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let name = match self.name.clone() {
             Some(n) => n,
             None => "".to_string(),
@@ -82,7 +194,7 @@ impl From<VirtualPort> for Port {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct PhysicalPort {
     pub guid: String,
     pub name: String,
@@ -90,7 +202,131 @@ pub struct PhysicalPort {
     pub system_id: String,
     pub lid: i32,
     pub system_name: String,
-    pub logical_state: String,
+    pub logical_state: LogicalState,
+    /// The physical state of the port, e.g. "Link Up".
+    #[serde(default)]
+    pub physical_state: Option<PhysicalState>,
+    /// The display name of the port, e.g. "hpc-cloud01 / HCA-1 / 1".
+    #[serde(default)]
+    pub dname: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// The port number in the system.
+    #[serde(default)]
+    pub number: Option<i32>,
+    #[serde(default)]
+    pub external_number: Option<i32>,
+    #[serde(default)]
+    pub module: Option<String>,
+    /// The active speed of the link, e.g. "HDR".
+    #[serde(default)]
+    pub active_speed: Option<String>,
+    /// The active width of the link, e.g. "4x".
+    #[serde(default)]
+    pub active_width: Option<String>,
+    #[serde(default, deserialize_with = "null_as_empty")]
+    pub enabled_speed: Vec<String>,
+    #[serde(default, deserialize_with = "null_as_empty")]
+    pub supported_speed: Vec<String>,
+    #[serde(default)]
+    pub max_supported_speed: Option<String>,
+    #[serde(default)]
+    pub mtu: Option<i32>,
+    /// The GUID of the port on the other side of the link.
+    #[serde(default)]
+    pub peer_guid: Option<String>,
+    #[serde(default)]
+    pub peer_lid: Option<i32>,
+    #[serde(default)]
+    pub peer_port_dname: Option<String>,
+    #[serde(default)]
+    pub peer_node_name: Option<String>,
+    /// The severity of the port, e.g. "Info", "Warning", "Minor", "Critical".
+    #[serde(default)]
+    pub severity: Option<String>,
+    #[serde(default)]
+    pub tier: Option<i32>,
+    #[serde(default, deserialize_with = "null_as_empty")]
+    pub capabilities: Vec<String>,
+}
+
+/// Deserialize the list which UFM reports as null for some ports as an empty one.
+fn null_as_empty<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    Ok(Option::<Vec<String>>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// The filter of ports, which is mapped to the query parameters of UFM; the ports are
+/// also filtered by the client in case some parameters are not supported by UFM.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct PortFilter {
+    /// The type of the systems, e.g. "Computer", "Switch".
+    pub sys_type: Option<String>,
+    /// The GUID of the system.
+    pub system: Option<String>,
+    /// The logical state of the ports.
+    pub state: Option<LogicalState>,
+    /// The LID of the port.
+    pub lid: Option<i32>,
+    /// The GUIDs of the ports, e.g. "0x1070FD0300176625" or "1070fd0300176625";
+    /// all ports if empty.
+    pub guids: Vec<String>,
+}
+
+impl PortFilter {
+    /// Build the query string of the filter, e.g. "?sys_type=Computer".
+    pub fn to_query(&self) -> String {
+        let mut query = url::form_urlencoded::Serializer::new(String::new());
+        if let Some(t) = &self.sys_type {
+            query.append_pair("sys_type", t);
+        }
+        if let Some(s) = &self.system {
+            query.append_pair("system", s);
+        }
+        if let Some(LogicalState::Active) = &self.state {
+            query.append_pair("active", "true");
+        }
+        if let Some(lid) = &self.lid {
+            query.append_pair("lid", &lid.to_string());
+        }
+        if !self.guids.is_empty() {
+            query.append_pair("guids", &self.guids.join(","));
+        }
+
+        match query.finish() {
+            q if q.is_empty() => q,
+            q => format!("?{}", q),
+        }
+    }
+
+    /// Check whether the port matches the filter; the sys_type is only filtered by UFM
+    /// as it's not part of the port.
+    pub fn matches(&self, port: &PhysicalPort) -> bool {
+        if let Some(s) = &self.system {
+            if !port.system_id.eq_ignore_ascii_case(s) {
+                return false;
+            }
+        }
+        if let Some(state) = &self.state {
+            if port.logical_state != *state {
+                return false;
+            }
+        }
+        if let Some(lid) = &self.lid {
+            if port.lid != *lid {
+                return false;
+            }
+        }
+        if !self.guids.is_empty()
+            && !self
+                .guids
+                .iter()
+                .any(|g| normalize_guid(g) == normalize_guid(&port.guid))
+        {
+            return false;
+        }
+
+        true
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -99,6 +335,68 @@ pub struct VirtualPort {
     pub system_guid: String,
     pub virtual_port_lid: i32,
     pub system_name: String,
-    pub virtual_port_state: String,
+    pub virtual_port_state: LogicalState,
     pub port_guid: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_unknown_state() {
+        assert_eq!(LogicalState::from("Unknown"), LogicalState::Unknown);
+        assert_eq!(LogicalState::from(""), LogicalState::Unknown);
+        assert_eq!(
+            LogicalState::from("Sleeping"),
+            LogicalState::Other("Sleeping".to_string())
+        );
+        assert_eq!(PhysicalState::from("Unknown"), PhysicalState::Unknown);
+        assert_eq!(LogicalState::Unknown.to_string(), "Unknown");
+        assert_eq!(LogicalState::from("ACTIVE"), LogicalState::Active);
+        assert_eq!(PhysicalState::from("Link Up"), PhysicalState::LinkUp);
+    }
+
+    fn port(guid: &str) -> PhysicalPort {
+        serde_json::from_value(serde_json::json!({
+            "guid": guid,
+            "name": format!("{}_1", guid),
+            "systemID": "1070fd0300176624",
+            "lid": 1,
+            "system_name": "host01",
+            "logical_state": "Active",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn match_guids() {
+        let filter = PortFilter {
+            guids: vec!["0x1070FD0300176625".to_string()],
+            ..PortFilter::default()
+        };
+        assert!(filter.matches(&port("1070fd0300176625")));
+        assert!(!filter.matches(&port("1070fd0300176626")));
+    }
+
+    #[test]
+    fn deserialize_null_lists() {
+        let port: PhysicalPort = serde_json::from_str(
+            r#"{
+                "guid": "1070fd0300176625",
+                "name": "1070fd0300176625_1",
+                "systemID": "1070fd0300176624",
+                "lid": 1,
+                "system_name": "host01",
+                "logical_state": "Active",
+                "enabled_speed": null,
+                "supported_speed": ["HDR"],
+                "capabilities": null
+            }"#,
+        )
+        .unwrap();
+        assert!(port.enabled_speed.is_empty());
+        assert_eq!(port.supported_speed, vec!["HDR"]);
+        assert!(port.capabilities.is_empty());
+    }
+}
@@ -0,0 +1,251 @@
+use std::collections::{BTreeSet, HashSet};
+use std::fmt::{self, Display};
+
+use serde::{Deserialize, Serialize};
+
+use crate::lookup::{is_guid, normalize_guid};
+use crate::{PhysicalPort, PortFilter, UFMError, Ufm};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PortAction {
+    Enable,
+    Disable,
+    Reset,
+    /// Clear the performance and error counters of the port.
+    #[serde(rename = "clear_counters")]
+    ClearCounters,
+}
+
+impl PortAction {
+    /// Whether the action brings the link down, so it's refused on switch-to-switch
+    /// links without force.
+    pub fn is_disruptive(&self) -> bool {
+        matches!(self, PortAction::Disable | PortAction::Reset)
+    }
+}
+
+impl Display for PortAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PortAction::Enable => f.pad("enable"),
+            PortAction::Disable => f.pad("disable"),
+            PortAction::Reset => f.pad("reset"),
+            PortAction::ClearCounters => f.pad("clear_counters"),
+        }
+    }
+}
+
+/// The job of a port action; the job ID is None if UFM does not return it, e.g. in dry-run mode.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PortActionJob {
+    pub action: PortAction,
+    /// The name of the port, e.g. "1070fd0300176624_2".
+    pub port: String,
+    pub job_id: Option<String>,
+}
+
+/// The job in UFM, e.g. of a port action.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Job {
+    #[serde(rename = "ID")]
+    pub id: String,
+    #[serde(rename = "Status", default)]
+    pub status: String,
+    #[serde(rename = "Progress", default)]
+    pub progress: i32,
+    #[serde(rename = "Description", default)]
+    pub description: String,
+    #[serde(rename = "Summary", default)]
+    pub summary: String,
+}
+
+impl Ufm {
+    /// Resolve the ports by their GUIDs or names; the GUIDs are queried in batches and
+    /// the names, e.g. "1070fd0300176624_2", by their systems, so all the ports are only
+    /// listed for the names of other formats.
+    pub async fn resolve_ports(&self, targets: &[String]) -> Result<Vec<PhysicalPort>, UFMError> {
+        let (guids, names): (Vec<String>, Vec<String>) =
+            targets.iter().cloned().partition(|t| is_guid(t));
+
+        let mut ports = self.get_ports(&guids).await?;
+        let systems: BTreeSet<Option<String>> = names
+            .iter()
+            .map(|n| {
+                n.rsplit_once('_')
+                    .map(|(s, _)| s)
+                    .filter(|s| is_guid(s))
+                    .map(normalize_guid)
+            })
+            .collect();
+        match systems.contains(&None) {
+            true => ports.extend(self.list_physical_ports(&PortFilter::default()).await?),
+            false => {
+                for system in systems.into_iter().flatten() {
+                    let filter = PortFilter {
+                        system: Some(system),
+                        ..PortFilter::default()
+                    };
+                    ports.extend(self.list_physical_ports(&filter).await?);
+                }
+            }
+        }
+
+        let mut res = vec![];
+        for t in targets {
+            let guid = normalize_guid(t);
+            let port = ports
+                .iter()
+                .find(|p| p.name == *t || normalize_guid(&p.guid) == guid)
+                .ok_or(UFMError::NotFound(t.to_string()))?;
+            res.push(port.clone());
+        }
+
+        Ok(res)
+    }
+
+    /// List the names of the ports which are switch-to-switch links.
+    pub async fn inter_switch_ports(
+        &self,
+        ports: &[PhysicalPort],
+    ) -> Result<Vec<String>, UFMError> {
+        // Only the ports and their peers are queried, as switch ports.
+        let guids: Vec<String> = ports
+            .iter()
+            .flat_map(|p| std::iter::once(&p.guid).chain(p.peer_guid.as_ref()))
+            .cloned()
+            .collect();
+        let filter = PortFilter {
+            sys_type: Some("Switch".to_string()),
+            guids,
+            ..PortFilter::default()
+        };
+        let switch_ports: HashSet<String> = match ports.is_empty() {
+            true => HashSet::new(),
+            false => self
+                .list_physical_ports(&filter)
+                .await?
+                .into_iter()
+                .map(|p| normalize_guid(&p.guid))
+                .collect(),
+        };
+
+        Ok(ports
+            .iter()
+            .filter(|p| switch_ports.contains(&normalize_guid(&p.guid)))
+            .filter(|p| match &p.peer_guid {
+                Some(peer) => switch_ports.contains(&normalize_guid(peer)),
+                None => false,
+            })
+            .map(|p| p.name.clone())
+            .collect())
+    }
+
+    pub async fn enable_ports(&self, targets: &[String]) -> Result<Vec<PortActionJob>, UFMError> {
+        let ports = self.resolve_ports(targets).await?;
+        self.port_action(PortAction::Enable, &ports, false).await
+    }
+
+    pub async fn disable_ports(
+        &self,
+        targets: &[String],
+        force: bool,
+    ) -> Result<Vec<PortActionJob>, UFMError> {
+        let ports = self.resolve_ports(targets).await?;
+        self.port_action(PortAction::Disable, &ports, force).await
+    }
+
+    pub async fn reset_ports(
+        &self,
+        targets: &[String],
+        force: bool,
+    ) -> Result<Vec<PortActionJob>, UFMError> {
+        let ports = self.resolve_ports(targets).await?;
+        self.port_action(PortAction::Reset, &ports, force).await
+    }
+
+    /// Run the action on the resolved ports and return the jobs; the disruptive actions
+    /// on switch-to-switch links are refused unless forced.
+    pub async fn port_action(
+        &self,
+        action: PortAction,
+        ports: &[PhysicalPort],
+        force: bool,
+    ) -> Result<Vec<PortActionJob>, UFMError> {
+        if action.is_disruptive() && !force {
+            let isl = self.inter_switch_ports(ports).await?;
+            if !isl.is_empty() {
+                return Err(UFMError::ProtectedPort(isl.join(", ")));
+            }
+        }
+
+        let path = String::from("/actions");
+
+        #[derive(Serialize, Deserialize, Debug)]
+        struct Params {
+            port_id: String,
+        }
+
+        #[derive(Serialize, Deserialize, Debug)]
+        struct Action {
+            params: Params,
+            action: PortAction,
+            object_ids: Vec<String>,
+            object_type: String,
+            description: String,
+            identifier: String,
+        }
+
+        #[derive(Serialize, Deserialize, Debug)]
+        struct ActionJob {
+            #[serde(alias = "job_id", alias = "ID")]
+            id: String,
+        }
+
+        let mut jobs = vec![];
+        for port in ports {
+            let data = serde_json::to_string(&Action {
+                params: Params {
+                    port_id: port.name.clone(),
+                },
+                action,
+                object_ids: vec![port.system_id.clone()],
+                object_type: "System".to_string(),
+                description: "".to_string(),
+                identifier: "id".to_string(),
+            })
+            .map_err(|_| UFMError::InvalidConfig("invalid port action".to_string()))?;
+
+            let resp = self.client.post_with_response(&path, data).await?;
+            let job: Option<ActionJob> = serde_json::from_str(&resp).ok();
+
+            jobs.push(PortActionJob {
+                action,
+                port: port.name.clone(),
+                job_id: job.map(|j| j.id),
+            });
+        }
+
+        Ok(jobs)
+    }
+
+    pub async fn get_job(&self, id: &str) -> Result<Job, UFMError> {
+        let path = format!("/jobs/{}", id);
+        let job: Job = self.client.get(&path).await?;
+
+        Ok(job)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disruptive_actions() {
+        assert!(PortAction::Disable.is_disruptive());
+        assert!(PortAction::Reset.is_disruptive());
+        assert!(!PortAction::Enable.is_disruptive());
+        assert!(!PortAction::ClearCounters.is_disruptive());
+    }
+}
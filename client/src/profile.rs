@@ -0,0 +1,233 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Mtu, Partition, PartitionKey, PartitionQoS, RateLimit, ServiceLevel, UFMError, Ufm};
+
+/// The named QoS presets of partitions, e.g. "storage".
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(transparent)]
+pub struct QoSProfiles {
+    profiles: BTreeMap<String, PartitionQoS>,
+}
+
+impl Default for QoSProfiles {
+    /// The builtin profiles: "storage" and "tenant-default".
+    fn default() -> Self {
+        let mut profiles = QoSProfiles::empty();
+        profiles.insert(
+            "storage",
+            PartitionQoS::builder()
+                .mtu_limit(Mtu::Mtu4K)
+                .service_level(ServiceLevel(1))
+                .rate_limit(RateLimit::Gbps100)
+                .build(),
+        );
+        profiles.insert("tenant-default", PartitionQoS::default());
+
+        profiles
+    }
+}
+
+impl QoSProfiles {
+    pub fn empty() -> Self {
+        QoSProfiles {
+            profiles: BTreeMap::new(),
+        }
+    }
+
+    /// Add or replace the profile.
+    pub fn insert(&mut self, name: &str, qos: PartitionQoS) {
+        self.profiles.insert(name.to_string(), qos);
+    }
+
+    /// Add or replace the profiles by the given ones, e.g. loaded from a config file.
+    pub fn merge(&mut self, other: QoSProfiles) {
+        self.profiles.extend(other.profiles);
+    }
+
+    pub fn get(&self, name: &str) -> Result<&PartitionQoS, UFMError> {
+        self.profiles
+            .get(name)
+            .ok_or(UFMError::NotFound(format!("qos profile {}", name)))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &PartitionQoS)> {
+        self.profiles.iter()
+    }
+
+    /// Check the QoS of all the profiles, e.g. after loading them from a config file.
+    pub fn validate(&self) -> Result<(), UFMError> {
+        for (name, qos) in &self.profiles {
+            qos.validate().map_err(|e| match e {
+                UFMError::InvalidConfig(m) => {
+                    UFMError::InvalidConfig(format!("{} in qos profile {}", m, name))
+                }
+                e => e,
+            })?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The partition whose live QoS deviates from its assigned profile.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct QoSDeviation {
+    pub pkey: PartitionKey,
+    pub name: String,
+    pub profile: String,
+    pub expected: PartitionQoS,
+    pub actual: PartitionQoS,
+}
+
+/// Check the QoS of the partitions against their assigned profiles; the partitions
+/// without assignment are skipped.
+pub fn check_qos_compliance(
+    partitions: &[Partition],
+    profiles: &QoSProfiles,
+    assignments: &BTreeMap<PartitionKey, String>,
+) -> Result<Vec<QoSDeviation>, UFMError> {
+    let mut deviations = vec![];
+    for p in partitions {
+        let profile = match assignments.get(&p.pkey) {
+            Some(profile) => profile,
+            None => continue,
+        };
+
+        let expected = profiles.get(profile)?;
+        if *expected != p.qos {
+            deviations.push(QoSDeviation {
+                pkey: p.pkey,
+                name: p.name.clone(),
+                profile: profile.clone(),
+                expected: expected.clone(),
+                actual: p.qos.clone(),
+            });
+        }
+    }
+    deviations.sort_by_key(|d| d.pkey);
+
+    Ok(deviations)
+}
+
+impl Ufm {
+    /// List the partitions whose live QoS deviates from their assigned profiles.
+    pub async fn check_qos_compliance(
+        &self,
+        profiles: &QoSProfiles,
+        assignments: &BTreeMap<PartitionKey, String>,
+    ) -> Result<Vec<QoSDeviation>, UFMError> {
+        let partitions = self.list_partition().await?;
+
+        check_qos_compliance(&partitions, profiles, assignments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pkey(k: &str) -> PartitionKey {
+        PartitionKey::try_from(k).unwrap()
+    }
+
+    fn partition(k: &str, qos: PartitionQoS) -> Partition {
+        Partition {
+            name: format!("p{}", k),
+            pkey: pkey(k),
+            ipoib: true,
+            qos,
+        }
+    }
+
+    fn storage() -> PartitionQoS {
+        PartitionQoS::builder()
+            .mtu_limit(Mtu::Mtu4K)
+            .service_level(ServiceLevel(1))
+            .rate_limit(RateLimit::Gbps100)
+            .build()
+    }
+
+    #[test]
+    fn compliant_partition() {
+        let partitions = vec![
+            partition("0x10", storage()),
+            partition("0x20", PartitionQoS::default()),
+        ];
+        let assignments = BTreeMap::from([(pkey("0x10"), "storage".to_string())]);
+
+        let deviations =
+            check_qos_compliance(&partitions, &QoSProfiles::default(), &assignments).unwrap();
+
+        assert!(deviations.is_empty());
+    }
+
+    #[test]
+    fn non_compliant_partition() {
+        let partitions = vec![
+            partition("0x10", PartitionQoS::default()),
+            partition("0x20", storage()),
+        ];
+        let assignments = BTreeMap::from([
+            (pkey("0x10"), "storage".to_string()),
+            (pkey("0x20"), "storage".to_string()),
+        ]);
+
+        let deviations =
+            check_qos_compliance(&partitions, &QoSProfiles::default(), &assignments).unwrap();
+
+        assert_eq!(deviations.len(), 1);
+        assert_eq!(deviations[0].pkey, pkey("0x10"));
+        assert_eq!(deviations[0].profile, "storage");
+        assert_eq!(deviations[0].expected, storage());
+        assert_eq!(deviations[0].actual, PartitionQoS::default());
+    }
+
+    #[test]
+    fn unknown_profile() {
+        let partitions = vec![partition("0x10", storage())];
+        let assignments = BTreeMap::from([(pkey("0x10"), "gold".to_string())]);
+
+        let res = check_qos_compliance(&partitions, &QoSProfiles::default(), &assignments);
+
+        assert!(matches!(res, Err(UFMError::NotFound(m)) if m.contains("gold")));
+    }
+
+    #[test]
+    fn override_builtin_profile() {
+        let mut profiles = QoSProfiles::default();
+        profiles.merge(
+            serde_json::from_str(
+                r#"{"storage": {"mtu_limit": 4, "service_level": 2, "rate_limit": 200}}"#,
+            )
+            .unwrap(),
+        );
+        let partitions = vec![partition("0x10", storage())];
+        let assignments = BTreeMap::from([(pkey("0x10"), "storage".to_string())]);
+
+        let deviations = check_qos_compliance(&partitions, &profiles, &assignments).unwrap();
+
+        assert_eq!(deviations.len(), 1);
+        assert_eq!(deviations[0].expected.service_level, ServiceLevel(2));
+        assert_eq!(deviations[0].expected.rate_limit, RateLimit::Gbps200);
+        assert_eq!(
+            profiles.get("tenant-default").unwrap(),
+            &PartitionQoS::default()
+        );
+    }
+
+    #[test]
+    fn reject_invalid_profile() {
+        let profiles: QoSProfiles = serde_json::from_str(
+            r#"{"storage": {"mtu_limit": 4, "service_level": 16, "rate_limit": 100}}"#,
+        )
+        .unwrap();
+
+        assert!(matches!(
+            profiles.validate(),
+            Err(UFMError::InvalidConfig(m)) if m.contains("storage")
+        ));
+        assert!(QoSProfiles::default().validate().is_ok());
+    }
+}
@@ -0,0 +1,345 @@
+use std::fmt::{self, Display};
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::UFMError;
+
+/// The MTU limit of a partition; UFM takes 2 for 2k and 4 for 4k.
+#[derive(Serialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[serde(into = "u16")]
+pub enum Mtu {
+    #[default]
+    Mtu2K,
+    Mtu4K,
+    /// An unknown MTU reported by UFM, kept as is.
+    Other(u16),
+}
+
+impl TryFrom<u16> for Mtu {
+    type Error = UFMError;
+
+    fn try_from(mtu: u16) -> Result<Self, Self::Error> {
+        match mtu {
+            2 | 2048 => Ok(Mtu::Mtu2K),
+            4 | 4096 => Ok(Mtu::Mtu4K),
+            _ => Err(UFMError::InvalidConfig(format!("invalid mtu {}", mtu))),
+        }
+    }
+}
+
+// The values from UFM are kept as is, so an unknown one does not fail the partition.
+impl<'de> Deserialize<'de> for Mtu {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mtu = u16::deserialize(deserializer)?;
+        Ok(Mtu::try_from(mtu).unwrap_or(Mtu::Other(mtu)))
+    }
+}
+
+impl From<Mtu> for u16 {
+    fn from(mtu: Mtu) -> u16 {
+        match mtu {
+            Mtu::Mtu2K => 2,
+            Mtu::Mtu4K => 4,
+            Mtu::Other(mtu) => mtu,
+        }
+    }
+}
+
+impl FromStr for Mtu {
+    type Err = UFMError;
+
+    fn from_str(mtu: &str) -> Result<Self, Self::Err> {
+        match mtu.trim().to_lowercase().as_str() {
+            "2k" => Ok(Mtu::Mtu2K),
+            "4k" => Ok(Mtu::Mtu4K),
+            m => Mtu::try_from(
+                m.parse::<u16>()
+                    .map_err(|_| UFMError::InvalidConfig(format!("invalid mtu {}", mtu)))?,
+            ),
+        }
+    }
+}
+
+impl Display for Mtu {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mtu::Mtu2K => f.pad("2k"),
+            Mtu::Mtu4K => f.pad("4k"),
+            Mtu::Other(mtu) => f.pad(&mtu.to_string()),
+        }
+    }
+}
+
+/// The rate limit of a partition in Gbps.
+#[derive(Serialize, Debug, Clone, Copy, Default)]
+#[serde(into = "f64")]
+pub enum RateLimit {
+    Gbps2_5,
+    Gbps5,
+    Gbps10,
+    Gbps14,
+    Gbps20,
+    Gbps25,
+    Gbps30,
+    Gbps40,
+    Gbps56,
+    Gbps60,
+    Gbps80,
+    #[default]
+    Gbps100,
+    Gbps112,
+    Gbps120,
+    Gbps168,
+    Gbps200,
+    Gbps300,
+    /// An unknown rate limit reported by UFM, kept as is.
+    Other(f64),
+}
+
+const RATE_LIMITS: [(RateLimit, f64); 17] = [
+    (RateLimit::Gbps2_5, 2.5),
+    (RateLimit::Gbps5, 5.0),
+    (RateLimit::Gbps10, 10.0),
+    (RateLimit::Gbps14, 14.0),
+    (RateLimit::Gbps20, 20.0),
+    (RateLimit::Gbps25, 25.0),
+    (RateLimit::Gbps30, 30.0),
+    (RateLimit::Gbps40, 40.0),
+    (RateLimit::Gbps56, 56.0),
+    (RateLimit::Gbps60, 60.0),
+    (RateLimit::Gbps80, 80.0),
+    (RateLimit::Gbps100, 100.0),
+    (RateLimit::Gbps112, 112.0),
+    (RateLimit::Gbps120, 120.0),
+    (RateLimit::Gbps168, 168.0),
+    (RateLimit::Gbps200, 200.0),
+    (RateLimit::Gbps300, 300.0),
+];
+
+impl TryFrom<f64> for RateLimit {
+    type Error = UFMError;
+
+    fn try_from(rate: f64) -> Result<Self, Self::Error> {
+        RATE_LIMITS
+            .iter()
+            .find(|(_, r)| (r - rate).abs() < f64::EPSILON)
+            .map(|(l, _)| *l)
+            .ok_or(UFMError::InvalidConfig(format!(
+                "invalid rate limit {}",
+                rate
+            )))
+    }
+}
+
+// The values from UFM are kept as is, so an unknown one does not fail the partition.
+impl<'de> Deserialize<'de> for RateLimit {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let rate = f64::deserialize(deserializer)?;
+        Ok(RateLimit::try_from(rate).unwrap_or(RateLimit::Other(rate)))
+    }
+}
+
+impl From<RateLimit> for f64 {
+    fn from(rate: RateLimit) -> f64 {
+        if let RateLimit::Other(r) = rate {
+            return r;
+        }
+
+        RATE_LIMITS
+            .iter()
+            .find(|(l, _)| std::mem::discriminant(l) == std::mem::discriminant(&rate))
+            .map(|(_, r)| *r)
+            .unwrap_or_default()
+    }
+}
+
+// Compare the rates in Gbps, so an unknown rate equals itself.
+impl PartialEq for RateLimit {
+    fn eq(&self, other: &Self) -> bool {
+        f64::from(*self).to_bits() == f64::from(*other).to_bits()
+    }
+}
+
+impl Eq for RateLimit {}
+
+impl Hash for RateLimit {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        f64::from(*self).to_bits().hash(state);
+    }
+}
+
+impl FromStr for RateLimit {
+    type Err = UFMError;
+
+    fn from_str(rate: &str) -> Result<Self, Self::Err> {
+        let r = rate
+            .trim()
+            .parse::<f64>()
+            .map_err(|_| UFMError::InvalidConfig(format!("invalid rate limit {}", rate)))?;
+
+        RateLimit::try_from(r)
+    }
+}
+
+impl Display for RateLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(&f64::from(*self).to_string())
+    }
+}
+
+/// The service level of a partition, in the range of 0-15; the values from UFM are
+/// kept as is.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[serde(into = "u8")]
+pub struct ServiceLevel(pub(crate) u8);
+
+impl TryFrom<u8> for ServiceLevel {
+    type Error = UFMError;
+
+    fn try_from(sl: u8) -> Result<Self, Self::Error> {
+        if sl > 15 {
+            return Err(UFMError::InvalidConfig(format!(
+                "invalid service level {}",
+                sl
+            )));
+        }
+
+        Ok(ServiceLevel(sl))
+    }
+}
+
+impl From<ServiceLevel> for u8 {
+    fn from(sl: ServiceLevel) -> u8 {
+        sl.0
+    }
+}
+
+impl FromStr for ServiceLevel {
+    type Err = UFMError;
+
+    fn from_str(sl: &str) -> Result<Self, Self::Err> {
+        let v = sl
+            .trim()
+            .parse::<u8>()
+            .map_err(|_| UFMError::InvalidConfig(format!("invalid service level {}", sl)))?;
+
+        ServiceLevel::try_from(v)
+    }
+}
+
+impl Display for ServiceLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(&self.0.to_string())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct PartitionQoS {
+    // Default 2k; one of 2k or 4k; the MTU of the services.
+    pub mtu_limit: Mtu,
+    // Default is 0, value can be range from 0-15
+    pub service_level: ServiceLevel,
+    // Default is 100, can be one of the following: 2.5, 10, 30, 5, 20, 40, 60, 80, 120, 14, 56, 112, 168, 25, 100, 200, or 300
+    pub rate_limit: RateLimit,
+}
+
+impl PartitionQoS {
+    pub fn builder() -> PartitionQoSBuilder {
+        PartitionQoSBuilder::default()
+    }
+
+    /// Check the QoS given by the user, e.g. in a config file; the unknown values are
+    /// only accepted from UFM.
+    pub fn validate(&self) -> Result<(), UFMError> {
+        if let Mtu::Other(mtu) = self.mtu_limit {
+            return Err(UFMError::InvalidConfig(format!("invalid mtu {}", mtu)));
+        }
+        if let RateLimit::Other(rate) = self.rate_limit {
+            return Err(UFMError::InvalidConfig(format!(
+                "invalid rate limit {}",
+                rate
+            )));
+        }
+        ServiceLevel::try_from(self.service_level.0)?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct PartitionQoSBuilder {
+    qos: PartitionQoS,
+}
+
+impl PartitionQoSBuilder {
+    pub fn mtu_limit(mut self, mtu: Mtu) -> Self {
+        self.qos.mtu_limit = mtu;
+        self
+    }
+
+    pub fn service_level(mut self, sl: ServiceLevel) -> Self {
+        self.qos.service_level = sl;
+        self
+    }
+
+    pub fn rate_limit(mut self, rate: RateLimit) -> Self {
+        self.qos.rate_limit = rate;
+        self
+    }
+
+    pub fn build(self) -> PartitionQoS {
+        self.qos
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_unknown_qos() {
+        let qos: PartitionQoS =
+            serde_json::from_str(r#"{"mtu_limit": 8, "service_level": 20, "rate_limit": 400.0}"#)
+                .unwrap();
+        assert_eq!(qos.mtu_limit, Mtu::Other(8));
+        assert_eq!(qos.service_level, ServiceLevel(20));
+        assert_eq!(qos.rate_limit, RateLimit::Other(400.0));
+
+        let data = serde_json::to_string(&qos).unwrap();
+        assert_eq!(serde_json::from_str::<PartitionQoS>(&data).unwrap(), qos);
+    }
+
+    #[test]
+    fn deserialize_known_qos() {
+        let qos: PartitionQoS =
+            serde_json::from_str(r#"{"mtu_limit": 4, "service_level": 1, "rate_limit": 2.5}"#)
+                .unwrap();
+        assert_eq!(qos.mtu_limit, Mtu::Mtu4K);
+        assert_eq!(qos.service_level, ServiceLevel(1));
+        assert_eq!(qos.rate_limit, RateLimit::Gbps2_5);
+    }
+
+    #[test]
+    fn validate_qos() {
+        assert!(PartitionQoS::default().validate().is_ok());
+
+        for data in [
+            r#"{"mtu_limit": 8, "service_level": 0, "rate_limit": 100}"#,
+            r#"{"mtu_limit": 2, "service_level": 200, "rate_limit": 100}"#,
+            r#"{"mtu_limit": 2, "service_level": 0, "rate_limit": 400}"#,
+        ] {
+            let qos: PartitionQoS = serde_json::from_str(data).unwrap();
+            assert!(matches!(qos.validate(), Err(UFMError::InvalidConfig(_))));
+        }
+    }
+
+    #[test]
+    fn parse_invalid_qos() {
+        assert!("8".parse::<Mtu>().is_err());
+        assert!("16".parse::<ServiceLevel>().is_err());
+        assert!("400".parse::<RateLimit>().is_err());
+        assert_eq!("100".parse::<RateLimit>().unwrap(), RateLimit::default());
+    }
+}
@@ -0,0 +1,619 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::{self, Display};
+
+use serde::{Deserialize, Serialize};
+
+use crate::lookup::normalize_guid;
+use crate::{Partition, PartitionKey, PartitionQoS, PortConfig, PortMembership, UFMError, Ufm};
+
+/// The desired partitions, e.g. loaded from a `partitions.yaml`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct PartitionSet {
+    pub partitions: Vec<PartitionSpec>,
+}
+
+/// The desired state of a partition.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PartitionSpec {
+    /// The name of Partition; UFM does not support renaming, so it's only used on creation.
+    #[serde(default)]
+    pub name: String,
+    /// The pkey of Partition.
+    pub pkey: PartitionKey,
+    /// Default false; like the name, it's only used on creation, and the live value is
+    /// kept for the existing partitions.
+    #[serde(default)]
+    pub ipoib: bool,
+    /// The QoS of Partition; the live QoS is kept if not set.
+    #[serde(default)]
+    pub qos: Option<PartitionQoS>,
+    /// The members of Partition.
+    #[serde(default)]
+    pub members: Vec<MemberSpec>,
+}
+
+/// The desired member of a partition.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MemberSpec {
+    /// The GUID of Port.
+    pub guid: String,
+    /// Default is full.
+    #[serde(default)]
+    pub membership: PortMembership,
+    /// Store the PKey at index 0 of the PKey table of the GUID; the live value is kept
+    /// if not set, and the new members default to true as `ufm create`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index0: Option<bool>,
+}
+
+impl From<PortConfig> for MemberSpec {
+    fn from(pc: PortConfig) -> Self {
+        MemberSpec {
+            guid: pc.guid,
+            membership: pc.membership,
+            index0: Some(pc.index0),
+        }
+    }
+}
+
+/// The live state of a partition in UFM.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PartitionState {
+    pub partition: Partition,
+    pub members: Vec<PortConfig>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum PlanAction {
+    /// Create the partition which does not exist in UFM.
+    Create { partition: Partition },
+    /// Add the ports to the partition, or change their membership.
+    Bind {
+        pkey: PartitionKey,
+        ipoib: bool,
+        ports: Vec<PortConfig>,
+    },
+    /// Remove the ports from the partition.
+    Unbind {
+        pkey: PartitionKey,
+        guids: Vec<String>,
+    },
+    /// Update the QoS of the partition.
+    UpdateQoS {
+        pkey: PartitionKey,
+        from: PartitionQoS,
+        to: PartitionQoS,
+    },
+    /// Delete the partition which is not in the desired set; only planned with prune.
+    Delete { pkey: PartitionKey },
+}
+
+impl Display for PlanAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlanAction::Create { partition } => write!(
+                f,
+                "+ create {} (name: {}, ipoib: {}, mtu: {}, sl: {}, rate: {})",
+                partition.pkey,
+                partition.name,
+                partition.ipoib,
+                partition.qos.mtu_limit,
+                partition.qos.service_level,
+                partition.qos.rate_limit
+            ),
+            PlanAction::Bind { pkey, ports, .. } => {
+                let ports: Vec<String> = ports
+                    .iter()
+                    .map(|p| format!("{}({})", p.guid, p.membership))
+                    .collect();
+                write!(f, "+ bind {}: {}", pkey, ports.join(", "))
+            }
+            PlanAction::Unbind { pkey, guids } => {
+                write!(f, "- unbind {}: {}", pkey, guids.join(", "))
+            }
+            PlanAction::UpdateQoS { pkey, from, to } => write!(
+                f,
+                "~ qos {}: mtu {} -> {}, sl {} -> {}, rate {} -> {}",
+                pkey,
+                from.mtu_limit,
+                to.mtu_limit,
+                from.service_level,
+                to.service_level,
+                from.rate_limit,
+                to.rate_limit
+            ),
+            PlanAction::Delete { pkey } => write!(f, "- delete {}", pkey),
+        }
+    }
+}
+
+/// The options of computing a plan.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+pub struct PlanOptions {
+    /// Delete the partitions which are not desired.
+    pub prune: bool,
+    /// Keep the live members which are not desired instead of unbinding them, e.g. to
+    /// merge the desired partitions into UFM.
+    pub keep_members: bool,
+}
+
+/// The ordered actions to reconcile the live partitions to the desired ones.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Plan {
+    pub actions: Vec<PlanAction>,
+}
+
+impl Plan {
+    /// Compute the actions which turn `live` into `desired`; the partitions which
+    /// are not in `desired` are deleted only with the `prune` option.
+    pub fn compute(
+        desired: &[PartitionSpec],
+        live: &[PartitionState],
+        opts: PlanOptions,
+    ) -> Result<Plan, UFMError> {
+        let mut wanted = BTreeMap::new();
+        for spec in desired {
+            if wanted.insert(spec.pkey, spec).is_some() {
+                return Err(UFMError::InvalidConfig(format!(
+                    "duplicated pkey {}",
+                    spec.pkey
+                )));
+            }
+            if let Some(qos) = &spec.qos {
+                qos.validate()?;
+            }
+            let mut guids = HashSet::new();
+            for m in &spec.members {
+                if !guids.insert(normalize_guid(&m.guid)) {
+                    return Err(UFMError::InvalidConfig(format!(
+                        "duplicated guid {} in pkey {}",
+                        m.guid, spec.pkey
+                    )));
+                }
+            }
+        }
+
+        let current: BTreeMap<PartitionKey, &PartitionState> =
+            live.iter().map(|s| (s.partition.pkey, s)).collect();
+
+        let mut actions = vec![];
+        for (pkey, spec) in &wanted {
+            let (state_qos, state_ipoib, state_members) = match current.get(pkey) {
+                Some(state) => (
+                    Some(&state.partition.qos),
+                    state.partition.ipoib,
+                    state.members.as_slice(),
+                ),
+                None => {
+                    let qos = spec.qos.clone().ok_or(UFMError::InvalidConfig(format!(
+                        "qos of new partition {} is required",
+                        pkey
+                    )))?;
+                    actions.push(PlanAction::Create {
+                        partition: Partition {
+                            name: spec.name.clone(),
+                            pkey: *pkey,
+                            ipoib: spec.ipoib,
+                            qos,
+                        },
+                    });
+                    (None, spec.ipoib, &[][..])
+                }
+            };
+
+            if let (Some(from), Some(to)) = (state_qos, &spec.qos) {
+                if from != to {
+                    actions.push(PlanAction::UpdateQoS {
+                        pkey: *pkey,
+                        from: from.clone(),
+                        to: to.clone(),
+                    });
+                }
+            }
+
+            let members: HashMap<String, &PortConfig> = state_members
+                .iter()
+                .map(|m| (normalize_guid(&m.guid), m))
+                .collect();
+
+            let ports: Vec<PortConfig> = spec
+                .members
+                .iter()
+                .filter_map(|m| match members.get(&normalize_guid(&m.guid)) {
+                    Some(c) => {
+                        let index0 = m.index0.unwrap_or(c.index0);
+                        (c.membership != m.membership || c.index0 != index0).then(|| PortConfig {
+                            guid: m.guid.clone(),
+                            membership: m.membership.clone(),
+                            index0,
+                        })
+                    }
+                    None => Some(PortConfig {
+                        guid: m.guid.clone(),
+                        membership: m.membership.clone(),
+                        index0: m.index0.unwrap_or(true),
+                    }),
+                })
+                .collect();
+            if !ports.is_empty() {
+                actions.push(PlanAction::Bind {
+                    pkey: *pkey,
+                    ipoib: state_ipoib,
+                    ports,
+                });
+            }
+
+            // All the ports are member of the default pkey, never unbind them.
+            if !pkey.is_default_pkey() && !opts.keep_members {
+                let wanted_guids: Vec<String> = spec
+                    .members
+                    .iter()
+                    .map(|m| normalize_guid(&m.guid))
+                    .collect();
+                let guids: Vec<String> = state_members
+                    .iter()
+                    .filter(|c| !wanted_guids.contains(&normalize_guid(&c.guid)))
+                    .map(|c| c.guid.clone())
+                    .collect();
+                if !guids.is_empty() {
+                    actions.push(PlanAction::Unbind { pkey: *pkey, guids });
+                }
+            }
+        }
+
+        if opts.prune {
+            for pkey in current.keys() {
+                if !wanted.contains_key(pkey) && !pkey.is_default_pkey() {
+                    actions.push(PlanAction::Delete { pkey: *pkey });
+                }
+            }
+        }
+
+        Ok(Plan { actions })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+}
+
+impl Ufm {
+    /// Read the live partitions and their members from UFM in one call.
+    pub async fn list_partition_state(&self) -> Result<Vec<PartitionState>, UFMError> {
+        #[derive(Serialize, Deserialize, Debug)]
+        struct PkeyWithGUIDs {
+            partition: String,
+            ip_over_ib: bool,
+            qos_conf: PartitionQoS,
+            #[serde(default)]
+            guids: Vec<PortConfig>,
+        }
+
+        let path = String::from("/resources/pkeys?guids_data=true&qos_conf=true");
+        let pkeys: HashMap<String, PkeyWithGUIDs> = self.client.list(&path).await?;
+
+        let mut res = vec![];
+        for (k, v) in pkeys {
+            res.push(PartitionState {
+                partition: Partition {
+                    name: v.partition,
+                    pkey: PartitionKey::try_from(&k)?,
+                    ipoib: v.ip_over_ib,
+                    qos: v.qos_conf,
+                },
+                members: v.guids,
+            });
+        }
+        res.sort_by_key(|s| s.partition.pkey);
+
+        Ok(res)
+    }
+
+    /// Compute the plan to reconcile UFM to the desired partitions.
+    pub async fn plan(
+        &self,
+        desired: &[PartitionSpec],
+        opts: PlanOptions,
+    ) -> Result<Plan, UFMError> {
+        let live = self.list_partition_state().await?;
+
+        Plan::compute(desired, &live, opts)
+    }
+
+    /// Apply the actions of the plan in order, and stop at the first failure.
+    pub async fn apply(&self, plan: &Plan) -> Result<(), UFMError> {
+        for action in &plan.actions {
+            log::info!("apply: {}", action);
+            match action {
+                PlanAction::Create { partition } => self.create_partition(partition).await?,
+                PlanAction::Bind { pkey, ipoib, ports } => {
+                    let p = Partition {
+                        name: "".to_string(),
+                        pkey: *pkey,
+                        ipoib: *ipoib,
+                        qos: PartitionQoS::default(),
+                    };
+                    self.bind_ports(p, ports.clone()).await?;
+                }
+                PlanAction::Unbind { pkey, guids } => {
+                    self.unbind_ports(*pkey, guids.clone(), false).await?;
+                }
+                PlanAction::UpdateQoS { pkey, to, .. } => {
+                    self.update_partition_qos(*pkey, to).await?
+                }
+                PlanAction::Delete { pkey } => {
+                    self.delete_partition(&pkey.to_string(), false).await?
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prune() -> PlanOptions {
+        PlanOptions {
+            prune: true,
+            ..PlanOptions::default()
+        }
+    }
+
+    fn pkey(k: &str) -> PartitionKey {
+        PartitionKey::try_from(k).unwrap()
+    }
+
+    fn member(guid: &str, membership: PortMembership, index0: Option<bool>) -> MemberSpec {
+        MemberSpec {
+            guid: guid.to_string(),
+            membership,
+            index0,
+        }
+    }
+
+    fn port(guid: &str, membership: PortMembership, index0: bool) -> PortConfig {
+        PortConfig {
+            guid: guid.to_string(),
+            membership,
+            index0,
+        }
+    }
+
+    fn spec(k: &str, members: Vec<MemberSpec>) -> PartitionSpec {
+        PartitionSpec {
+            name: format!("p{}", k),
+            pkey: pkey(k),
+            ipoib: true,
+            qos: Some(PartitionQoS::default()),
+            members,
+        }
+    }
+
+    fn state(k: &str, members: Vec<PortConfig>) -> PartitionState {
+        PartitionState {
+            partition: Partition {
+                name: format!("p{}", k),
+                pkey: pkey(k),
+                ipoib: true,
+                qos: PartitionQoS::default(),
+            },
+            members,
+        }
+    }
+
+    #[test]
+    fn create_missing_partition() {
+        let desired = vec![spec("0x10", vec![member("a", PortMembership::Full, None)])];
+        let plan = Plan::compute(&desired, &[], PlanOptions::default()).unwrap();
+
+        assert_eq!(plan.actions.len(), 2);
+        match &plan.actions[0] {
+            PlanAction::Create { partition } => {
+                assert_eq!(partition.pkey, pkey("0x10"));
+                assert_eq!(partition.name, "p0x10");
+            }
+            a => panic!("unexpected action {}", a),
+        }
+        match &plan.actions[1] {
+            PlanAction::Bind { ports, .. } => {
+                assert_eq!(ports, &vec![port("a", PortMembership::Full, true)])
+            }
+            a => panic!("unexpected action {}", a),
+        }
+    }
+
+    #[test]
+    fn create_requires_qos() {
+        let mut desired = spec("0x10", vec![]);
+        desired.qos = None;
+
+        assert!(Plan::compute(&[desired], &[], PlanOptions::default()).is_err());
+    }
+
+    #[test]
+    fn bind_new_and_changed_members() {
+        let desired = vec![spec(
+            "0x10",
+            vec![
+                member("a", PortMembership::Full, None),
+                member("b", PortMembership::Limited, None),
+                member("c", PortMembership::Full, Some(false)),
+            ],
+        )];
+        let live = vec![state(
+            "0x10",
+            vec![
+                port("a", PortMembership::Full, true),
+                port("b", PortMembership::Full, false),
+            ],
+        )];
+        let plan = Plan::compute(&desired, &live, PlanOptions::default()).unwrap();
+
+        assert_eq!(plan.actions.len(), 1);
+        match &plan.actions[0] {
+            PlanAction::Bind { ports, .. } => assert_eq!(
+                ports,
+                &vec![
+                    port("b", PortMembership::Limited, false),
+                    port("c", PortMembership::Full, false),
+                ]
+            ),
+            a => panic!("unexpected action {}", a),
+        }
+    }
+
+    #[test]
+    fn unbind_removed_members() {
+        let desired = vec![spec("0x10", vec![member("a", PortMembership::Full, None)])];
+        let live = vec![state(
+            "0x10",
+            vec![
+                port("a", PortMembership::Full, true),
+                port("b", PortMembership::Full, true),
+            ],
+        )];
+        let plan = Plan::compute(&desired, &live, PlanOptions::default()).unwrap();
+
+        assert_eq!(plan.actions.len(), 1);
+        match &plan.actions[0] {
+            PlanAction::Unbind { pkey: k, guids } => {
+                assert_eq!(*k, pkey("0x10"));
+                assert_eq!(guids, &vec!["b".to_string()]);
+            }
+            a => panic!("unexpected action {}", a),
+        }
+    }
+
+    #[test]
+    fn keep_members_on_merge() {
+        let desired = vec![spec("0x10", vec![member("a", PortMembership::Full, None)])];
+        let live = vec![state(
+            "0x10",
+            vec![
+                port("a", PortMembership::Full, true),
+                port("b", PortMembership::Full, true),
+            ],
+        )];
+        let opts = PlanOptions {
+            keep_members: true,
+            ..PlanOptions::default()
+        };
+
+        assert!(Plan::compute(&desired, &live, opts).unwrap().is_empty());
+    }
+
+    #[test]
+    fn never_unbind_default_pkey() {
+        let desired = vec![spec("0x7fff", vec![])];
+        let live = vec![state("0x7fff", vec![port("a", PortMembership::Full, true)])];
+
+        assert!(Plan::compute(&desired, &live, prune()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn prune_deletes_unwanted_partitions() {
+        let desired = vec![spec("0x10", vec![])];
+        let live = vec![
+            state("0x10", vec![]),
+            state("0x20", vec![]),
+            state("0x7fff", vec![]),
+        ];
+
+        assert!(Plan::compute(&desired, &live, PlanOptions::default())
+            .unwrap()
+            .is_empty());
+
+        let plan = Plan::compute(&desired, &live, prune()).unwrap();
+        assert_eq!(plan.actions.len(), 1);
+        match &plan.actions[0] {
+            PlanAction::Delete { pkey: k } => assert_eq!(*k, pkey("0x20")),
+            a => panic!("unexpected action {}", a),
+        }
+    }
+
+    #[test]
+    fn no_op_on_identical_state() {
+        let desired = vec![spec(
+            "0x10",
+            vec![
+                member("0x0002C90300A1B2C3", PortMembership::Full, None),
+                member("b", PortMembership::Limited, Some(false)),
+            ],
+        )];
+        let live = vec![state(
+            "0x10",
+            vec![
+                port("0002c90300a1b2c3", PortMembership::Full, true),
+                port("b", PortMembership::Limited, false),
+            ],
+        )];
+
+        assert!(Plan::compute(&desired, &live, PlanOptions::default())
+            .unwrap()
+            .is_empty());
+        assert!(Plan::compute(&desired, &live, prune()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn update_qos() {
+        let mut desired = spec("0x10", vec![]);
+        desired.qos = Some(PartitionQoS::builder().mtu_limit(crate::Mtu::Mtu4K).build());
+        let plan =
+            Plan::compute(&[desired], &[state("0x10", vec![])], PlanOptions::default()).unwrap();
+
+        assert_eq!(plan.actions.len(), 1);
+        assert!(matches!(plan.actions[0], PlanAction::UpdateQoS { .. }));
+    }
+
+    #[test]
+    fn reject_duplicated_pkey() {
+        let desired = vec![spec("0x10", vec![]), spec("0x10", vec![])];
+
+        assert!(Plan::compute(&desired, &[], PlanOptions::default()).is_err());
+    }
+
+    #[test]
+    fn reject_duplicated_guid() {
+        let desired = vec![spec(
+            "0x10",
+            vec![
+                member("a", PortMembership::Full, None),
+                member("0xA", PortMembership::Limited, None),
+            ],
+        )];
+
+        assert!(Plan::compute(&desired, &[], PlanOptions::default()).is_err());
+    }
+
+    #[test]
+    fn reject_invalid_qos() {
+        let mut desired = spec("0x10", vec![]);
+        desired.qos = Some(
+            serde_json::from_str(r#"{"mtu_limit": 8, "service_level": 0, "rate_limit": 100}"#)
+                .unwrap(),
+        );
+
+        assert!(matches!(
+            Plan::compute(&[desired], &[], PlanOptions::default()),
+            Err(UFMError::InvalidConfig(_))
+        ));
+    }
+
+    #[test]
+    fn keep_live_ipoib() {
+        let mut desired = spec("0x10", vec![member("a", PortMembership::Full, None)]);
+        desired.ipoib = false;
+        let plan =
+            Plan::compute(&[desired], &[state("0x10", vec![])], PlanOptions::default()).unwrap();
+
+        assert_eq!(plan.actions.len(), 1);
+        match &plan.actions[0] {
+            PlanAction::Bind { ipoib, .. } => assert!(ipoib),
+            a => panic!("unexpected action {}", a),
+        }
+    }
+}
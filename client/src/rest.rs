@@ -1,5 +1,6 @@
 use std::fmt;
 use std::fmt::{Display, Formatter};
+use std::sync::Mutex;
 use std::time::Duration;
 
 use hyper::client::HttpConnector;
@@ -8,6 +9,7 @@ use hyper::http::StatusCode;
 use hyper::{Body, Client, Method, Uri};
 use hyper_rustls::HttpsConnector;
 use hyper_timeout::TimeoutConnector;
+use serde::{Deserialize, Serialize};
 use std::time::SystemTime;
 use thiserror::Error;
 use tokio_rustls::rustls;
@@ -46,6 +48,12 @@ pub enum RestError {
     InvalidConfig(String),
 }
 
+impl RestError {
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, RestError::NotFound(_))
+    }
+}
+
 impl From<hyper::Error> for RestError {
     fn from(value: hyper::Error) -> Self {
         if value.is_user() {
@@ -81,18 +89,38 @@ impl Display for RestScheme {
     }
 }
 
+/// The request which would be sent to UFM in dry-run mode.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DryRunRecord {
+    pub method: String,
+    pub path: String,
+    pub body: Option<serde_json::Value>,
+}
+
+impl Display for DryRunRecord {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.body {
+            Some(b) => write!(f, "{} {} {}", self.method, self.path, b),
+            None => write!(f, "{} {}", self.method, self.path),
+        }
+    }
+}
+
 pub struct RestClientConfig {
     pub address: String,
     pub port: Option<u16>,
     pub scheme: RestScheme,
     pub auth_info: String,
     pub base_path: String,
+    pub dry_run: bool,
 }
 
 pub struct RestClient {
     base_url: String,
     auth_info: String,
     scheme: RestScheme,
+    dry_run: bool,
+    dry_run_records: Mutex<Vec<DryRunRecord>>,
     http_client: hyper::Client<TimeoutConnector<HttpConnector>>,
     https_client: hyper::Client<TimeoutConnector<HttpsConnector<HttpConnector>>>,
 }
@@ -146,6 +174,8 @@ impl RestClient {
             base_url,
             auth_info,
             scheme: conf.scheme.clone(),
+            dry_run: conf.dry_run,
+            dry_run_records: Mutex::new(vec![]),
             // TODO(k82cn): Add timout for the clients.
             http_client: Client::builder().build::<_, hyper::Body>(http_connector),
             https_client: Client::builder().build::<_, hyper::Body>(https_connector),
@@ -184,24 +214,83 @@ impl RestClient {
         Ok(())
     }
 
+    /// Post the data and return the response body, e.g. the job of an action; the body
+    /// is empty in dry-run mode.
+    pub async fn post_with_response(&self, path: &str, data: String) -> Result<String, RestError> {
+        self.execute_request(Method::POST, path, Some(data)).await
+    }
+
+    /// Post the query, e.g. of the monitoring data, and parse the response; the query
+    /// does not change UFM, so it is also sent in dry-run mode.
+    pub async fn query<'a, T: serde::de::DeserializeOwned>(
+        &'a self,
+        path: &'a str,
+        data: String,
+    ) -> Result<T, RestError> {
+        let resp = self.send_request(Method::POST, path, Some(data)).await?;
+        let data = serde_json::from_str(&resp)
+            .map_err(|_| RestError::InvalidConfig("invalid response".to_string()))?;
+
+        Ok(data)
+    }
+
     pub async fn delete(&self, path: &str) -> Result<(), RestError> {
         self.execute_request(Method::DELETE, path, None).await?;
 
         Ok(())
     }
 
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Take the requests recorded in dry-run mode.
+    pub fn take_dry_run_records(&self) -> Vec<DryRunRecord> {
+        match self.dry_run_records.lock() {
+            Ok(mut records) => records.drain(..).collect(),
+            Err(_) => vec![],
+        }
+    }
+
     async fn execute_request(
         &self,
         method: Method,
         path: &str,
         data: Option<String>,
+    ) -> Result<String, RestError> {
+        // Only record the requests which change UFM in dry-run mode.
+        if self.dry_run && method != Method::GET {
+            let uri = format!("{}/{}", self.base_url, path.trim_matches('/'))
+                .parse::<Uri>()
+                .map_err(|_| RestError::InvalidConfig("invalid path".to_string()))?;
+            let record = DryRunRecord {
+                method: method.to_string(),
+                path: uri.path().to_string(),
+                body: data.as_ref().and_then(|d| serde_json::from_str(d).ok()),
+            };
+            log::info!("would send: {}", record);
+            if let Ok(mut records) = self.dry_run_records.lock() {
+                records.push(record);
+            }
+
+            return Ok(String::new());
+        }
+
+        self.send_request(method, path, data).await
+    }
+
+    async fn send_request(
+        &self,
+        method: Method,
+        path: &str,
+        data: Option<String>,
     ) -> Result<String, RestError> {
         let url = format!("{}/{}", self.base_url, path.trim_matches('/'));
         let uri = url
             .parse::<Uri>()
             .map_err(|_| RestError::InvalidConfig("invalid path".to_string()))?;
 
-        let body = data.unwrap_or(String::new());
+        let body = data.unwrap_or_default();
         log::debug!("Method: {method}, URL: {url}, Body: {body}");
 
         let req = hyper::Request::builder()
@@ -222,7 +311,9 @@ impl RestClient {
         let data = String::from_utf8(chunk.to_vec()).unwrap();
 
         match status {
-            StatusCode::OK => Ok(data),
+            // The actions, e.g. port reset, are accepted as jobs.
+            StatusCode::OK | StatusCode::ACCEPTED => Ok(data),
+            StatusCode::NOT_FOUND => Err(RestError::NotFound(data)),
             _ => Err(RestError::Unknown(data)),
         }
     }
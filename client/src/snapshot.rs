@@ -0,0 +1,81 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{MemberSpec, PartitionSpec, Plan, PlanOptions, UFMError, Ufm};
+
+/// The version of the snapshot document generated by `export_partitions`.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// The snapshot of all the partitions in UFM, e.g. for backup and restore.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PartitionSnapshot {
+    pub version: u32,
+    pub partitions: Vec<PartitionSpec>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportMode {
+    /// Create/update the partitions and members in the snapshot, keep the others.
+    Merge,
+    /// Make UFM the same as the snapshot: the partitions and members which are not
+    /// in the snapshot are removed.
+    Replace,
+}
+
+impl Ufm {
+    /// Export all the partitions with their QoS and members; the default pkey is
+    /// skipped as all the ports are member of it, and it is never changed by import.
+    pub async fn export_partitions(&self) -> Result<PartitionSnapshot, UFMError> {
+        let mut partitions = vec![];
+        for state in self.list_partition_state().await? {
+            if state.partition.pkey.is_default_pkey() {
+                continue;
+            }
+
+            partitions.push(PartitionSpec {
+                name: state.partition.name,
+                pkey: state.partition.pkey,
+                ipoib: state.partition.ipoib,
+                qos: Some(state.partition.qos),
+                members: state.members.into_iter().map(MemberSpec::from).collect(),
+            });
+        }
+        partitions.sort_by_key(|p| p.pkey);
+
+        Ok(PartitionSnapshot {
+            version: SNAPSHOT_VERSION,
+            partitions,
+        })
+    }
+
+    /// Restore the partitions from the snapshot, and return the applied plan; the
+    /// default pkey in the snapshot is ignored.
+    pub async fn import_partitions(
+        &self,
+        snapshot: &PartitionSnapshot,
+        mode: ImportMode,
+    ) -> Result<Plan, UFMError> {
+        if snapshot.version != SNAPSHOT_VERSION {
+            return Err(UFMError::InvalidConfig(format!(
+                "unsupported snapshot version {}",
+                snapshot.version
+            )));
+        }
+
+        let partitions: Vec<PartitionSpec> = snapshot
+            .partitions
+            .iter()
+            .filter(|p| !p.pkey.is_default_pkey())
+            .cloned()
+            .collect();
+        let opts = PlanOptions {
+            prune: mode == ImportMode::Replace,
+            keep_members: mode == ImportMode::Merge,
+        };
+        let plan = self.plan(&partitions, opts).await?;
+
+        self.apply(&plan).await?;
+
+        Ok(plan)
+    }
+}
@@ -0,0 +1,212 @@
+use std::fmt::{self, Display};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::lookup::{is_guid, normalize_guid};
+use crate::port::state_serde;
+use crate::{UFMError, Ufm};
+
+/// The type of a system; the unrecognized types reported by UFM are kept as is.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SystemType {
+    Switch,
+    Host,
+    Gateway,
+    Router,
+    Unknown,
+    Other(String),
+}
+
+impl From<&str> for SystemType {
+    fn from(t: &str) -> Self {
+        match t.trim().to_lowercase().as_str() {
+            "switch" => SystemType::Switch,
+            "host" | "computer" => SystemType::Host,
+            "gateway" => SystemType::Gateway,
+            "router" => SystemType::Router,
+            "" | "unknown" => SystemType::Unknown,
+            _ => SystemType::Other(t.to_string()),
+        }
+    }
+}
+
+impl Display for SystemType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SystemType::Switch => f.pad("switch"),
+            SystemType::Host => f.pad("host"),
+            SystemType::Gateway => f.pad("gateway"),
+            SystemType::Router => f.pad("router"),
+            SystemType::Unknown => f.pad("unknown"),
+            SystemType::Other(s) => f.pad(s),
+        }
+    }
+}
+
+state_serde!(SystemType);
+
+/// The system in the fabric, e.g. a switch or a host.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct System {
+    #[serde(rename = "system_guid")]
+    pub guid: String,
+    #[serde(rename = "system_name")]
+    pub name: String,
+    #[serde(rename = "type")]
+    pub system_type: SystemType,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub vendor: Option<String>,
+    #[serde(default, rename = "fw_version")]
+    pub firmware_version: Option<String>,
+    #[serde(default, rename = "sw_version")]
+    pub software_version: Option<String>,
+    #[serde(default)]
+    pub ip: Option<String>,
+    /// The state of the system, e.g. "active".
+    #[serde(default)]
+    pub state: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub severity: Option<String>,
+    /// The rack of the system, where UFM reports it.
+    #[serde(default)]
+    pub rack: Option<String>,
+    /// The names of the ports of the system.
+    #[serde(default)]
+    pub ports: Vec<String>,
+}
+
+impl System {
+    pub fn port_count(&self) -> usize {
+        self.ports.len()
+    }
+}
+
+/// The filter of systems, which is mapped to the query parameters of UFM; the systems
+/// are also filtered by the client in case some parameters are not supported by UFM.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SystemFilter {
+    pub system_type: Option<SystemType>,
+    /// The model of the systems, e.g. "MQM8700".
+    pub model: Option<String>,
+    pub ip: Option<String>,
+    pub rack: Option<String>,
+}
+
+impl SystemFilter {
+    /// Build the query string of the filter, e.g. "?type=switch".
+    pub fn to_query(&self) -> String {
+        let mut query = url::form_urlencoded::Serializer::new(String::new());
+        if let Some(t) = &self.system_type {
+            query.append_pair("type", &t.to_string());
+        }
+        if let Some(m) = &self.model {
+            query.append_pair("model", m);
+        }
+        if let Some(ip) = &self.ip {
+            query.append_pair("ip", ip);
+        }
+
+        match query.finish() {
+            q if q.is_empty() => q,
+            q => format!("?{}", q),
+        }
+    }
+
+    /// Check whether the system matches the filter.
+    pub fn matches(&self, system: &System) -> bool {
+        if let Some(t) = &self.system_type {
+            if system.system_type != *t {
+                return false;
+            }
+        }
+        if let Some(m) = &self.model {
+            if !system
+                .model
+                .as_ref()
+                .is_some_and(|v| v.eq_ignore_ascii_case(m))
+            {
+                return false;
+            }
+        }
+        if let Some(ip) = &self.ip {
+            if system.ip.as_ref() != Some(ip) {
+                return false;
+            }
+        }
+        if let Some(rack) = &self.rack {
+            if system.rack.as_ref() != Some(rack) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+impl Ufm {
+    pub async fn list_systems(&self, filter: &SystemFilter) -> Result<Vec<System>, UFMError> {
+        let path = format!("/resources/systems{}", filter.to_query());
+        let systems: Vec<System> = self.client.list(&path).await?;
+
+        Ok(systems.into_iter().filter(|s| filter.matches(s)).collect())
+    }
+
+    /// Get the system by its GUID or name; only the systems looked up by name are
+    /// found in the list of all systems.
+    pub async fn get_system(&self, id: &str) -> Result<System, UFMError> {
+        let systems: Vec<System> = match is_guid(id) {
+            true => {
+                let path = format!("/resources/systems/{}", normalize_guid(id));
+                self.client.list(&path).await?
+            }
+            false => self
+                .list_systems(&SystemFilter::default())
+                .await?
+                .into_iter()
+                .filter(|s| s.name == id)
+                .collect(),
+        };
+
+        systems
+            .into_iter()
+            .next()
+            .ok_or(UFMError::NotFound(id.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_system() {
+        let system: System = serde_json::from_str(
+            r#"{
+                "system_guid": "1070fd0300176624",
+                "system_name": "host01",
+                "type": "computer",
+                "ip": "10.0.0.1",
+                "rack": "r01"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(system.guid, "1070fd0300176624");
+        assert_eq!(system.system_type, SystemType::Host);
+        assert_eq!(system.ip.as_deref(), Some("10.0.0.1"));
+        assert_eq!(system.rack.as_deref(), Some("r01"));
+    }
+
+    #[test]
+    fn parse_unknown_type() {
+        assert_eq!(SystemType::from("Unknown"), SystemType::Unknown);
+        assert_eq!(SystemType::from(""), SystemType::Unknown);
+        assert_eq!(
+            SystemType::from("Bridge"),
+            SystemType::Other("Bridge".to_string())
+        );
+    }
+}
@@ -0,0 +1,480 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+use crate::lookup::normalize_guid;
+use crate::{
+    LogicalState, PhysicalPort, PortFilter, System, SystemFilter, SystemType, UFMError, Ufm,
+};
+
+/// The link between two ports in the fabric.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Link {
+    /// The GUID of the system on the source side.
+    pub source_guid: String,
+    /// The port number in the source system.
+    #[serde(rename = "source_port")]
+    pub source_port_number: i32,
+    /// The display name of the source port, e.g. "switch-01 / 1".
+    #[serde(default)]
+    pub source_port_dname: Option<String>,
+    #[serde(default, rename = "source_port_node_description")]
+    pub source_system: Option<String>,
+    pub destination_guid: String,
+    #[serde(rename = "destination_port")]
+    pub destination_port_number: i32,
+    #[serde(default)]
+    pub destination_port_dname: Option<String>,
+    #[serde(default, rename = "destination_port_node_description")]
+    pub destination_system: Option<String>,
+    /// The width of the link, e.g. "4x".
+    #[serde(default)]
+    pub width: Option<String>,
+    /// The speed of the link, e.g. "HDR".
+    #[serde(default)]
+    pub speed: Option<String>,
+    #[serde(default)]
+    pub state: Option<LogicalState>,
+    /// The severity of the link, e.g. "Info", "Warning", "Minor", "Critical".
+    #[serde(default)]
+    pub severity: Option<String>,
+}
+
+/// The filter of the topology, e.g. to make the graph of a large fabric readable.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct TopologyFilter {
+    /// Only keep the systems in the rack.
+    pub rack: Option<String>,
+    /// Only keep the switches in the tiers, and the hosts linked to them; all tiers if empty.
+    pub tiers: Vec<i32>,
+    /// Only keep the systems of the GUIDs, e.g. the systems of the partition members, and
+    /// the switches they are linked to; a port GUID is resolved to its system.
+    pub systems: Option<Vec<String>>,
+}
+
+/// The graph of the fabric, with the systems as nodes and the links as edges; the
+/// systems are keyed by their normalized GUIDs.
+#[derive(Debug, Clone, Default)]
+pub struct Topology {
+    systems: HashMap<String, System>,
+    links: Vec<Link>,
+    /// The system GUID of each port GUID.
+    ports: HashMap<String, String>,
+    /// The tier of each system, where UFM reports it for its ports.
+    tiers: HashMap<String, i32>,
+    /// The neighbors of each system, with the index of the link to them.
+    adjacency: HashMap<String, Vec<(String, usize)>>,
+}
+
+impl Topology {
+    pub fn new(systems: Vec<System>, ports: Vec<PhysicalPort>, links: Vec<Link>) -> Self {
+        let tiers = ports
+            .iter()
+            .filter_map(|p| p.tier.map(|t| (normalize_guid(&p.system_id), t)))
+            .collect();
+        let ports = ports
+            .into_iter()
+            .map(|p| (normalize_guid(&p.guid), normalize_guid(&p.system_id)))
+            .collect();
+
+        Topology::build(systems, ports, tiers, links)
+    }
+
+    fn build(
+        systems: Vec<System>,
+        ports: HashMap<String, String>,
+        tiers: HashMap<String, i32>,
+        links: Vec<Link>,
+    ) -> Self {
+        let mut topo = Topology {
+            systems: systems
+                .into_iter()
+                .map(|s| (normalize_guid(&s.guid), s))
+                .collect(),
+            ports,
+            tiers,
+            ..Topology::default()
+        };
+
+        for (i, link) in links.iter().enumerate() {
+            let src = topo.system_guid(&link.source_guid);
+            let dst = topo.system_guid(&link.destination_guid);
+            topo.adjacency
+                .entry(src.clone())
+                .or_default()
+                .push((dst.clone(), i));
+            topo.adjacency.entry(dst).or_default().push((src, i));
+        }
+        topo.links = links;
+
+        topo
+    }
+
+    /// Map the GUID of a system or a port to the GUID of the system.
+    fn system_guid(&self, guid: &str) -> String {
+        let guid = normalize_guid(guid);
+        match self.ports.get(&guid) {
+            Some(s) if !self.systems.contains_key(&guid) => s.clone(),
+            _ => guid,
+        }
+    }
+
+    /// Resolve the system by its GUID, name or the GUID of one of its ports.
+    pub fn resolve(&self, id: &str) -> Option<&System> {
+        self.systems
+            .get(&self.system_guid(id))
+            .or_else(|| self.systems.values().find(|s| s.name == id))
+    }
+
+    pub fn systems(&self) -> impl Iterator<Item = &System> {
+        self.systems.values()
+    }
+
+    pub fn links(&self) -> &[Link] {
+        &self.links
+    }
+
+    /// The tier of the system, e.g. 1 for the leaf switches.
+    pub fn tier(&self, system: &System) -> Option<i32> {
+        self.tiers.get(&normalize_guid(&system.guid)).copied()
+    }
+
+    /// The systems on both sides of the link.
+    pub fn endpoints(&self, link: &Link) -> (Option<&System>, Option<&System>) {
+        (
+            self.systems.get(&self.system_guid(&link.source_guid)),
+            self.systems.get(&self.system_guid(&link.destination_guid)),
+        )
+    }
+
+    /// Build the sub-topology of the systems which match the filter.
+    pub fn filter(&self, filter: &TopologyFilter) -> Topology {
+        let mut keep: HashSet<String> = self.systems.keys().cloned().collect();
+
+        if let Some(systems) = &filter.systems {
+            let hosts: HashSet<String> = systems.iter().map(|g| self.system_guid(g)).collect();
+            let leaves: Vec<String> = hosts
+                .iter()
+                .flat_map(|h| self.adjacency.get(h).into_iter().flatten())
+                .map(|(n, _)| n.clone())
+                .filter(|n| self.is_switch(n))
+                .collect();
+            keep.retain(|s| hosts.contains(s) || leaves.contains(s));
+        }
+        if let Some(rack) = &filter.rack {
+            keep.retain(|s| self.systems[s].rack.as_ref() == Some(rack));
+        }
+        if !filter.tiers.is_empty() {
+            keep.retain(|s| {
+                !self.is_switch(s) || self.tiers.get(s).is_some_and(|t| filter.tiers.contains(t))
+            });
+            // Only keep the hosts which are linked to the remaining switches.
+            let linked: HashSet<String> = keep
+                .iter()
+                .filter(|s| self.is_switch(s))
+                .flat_map(|s| self.adjacency.get(s).into_iter().flatten())
+                .map(|(n, _)| n.clone())
+                .collect();
+            keep.retain(|s| self.is_switch(s) || linked.contains(s));
+        }
+
+        let links = self
+            .links
+            .iter()
+            .filter(|l| {
+                keep.contains(&self.system_guid(&l.source_guid))
+                    && keep.contains(&self.system_guid(&l.destination_guid))
+            })
+            .cloned()
+            .collect();
+
+        Topology::build(
+            self.systems
+                .values()
+                .filter(|s| keep.contains(&normalize_guid(&s.guid)))
+                .cloned()
+                .collect(),
+            self.ports.clone(),
+            self.tiers.clone(),
+            links,
+        )
+    }
+
+    fn is_switch(&self, guid: &str) -> bool {
+        self.systems
+            .get(guid)
+            .is_some_and(|s| s.system_type == SystemType::Switch)
+    }
+
+    /// List the links of the system, which is a GUID or name.
+    pub fn links_of(&self, id: &str) -> Vec<&Link> {
+        let Some(s) = self.resolve(id) else {
+            return vec![];
+        };
+
+        self.adjacency
+            .get(&normalize_guid(&s.guid))
+            .into_iter()
+            .flatten()
+            .map(|(_, i)| &self.links[*i])
+            .collect()
+    }
+
+    /// List the systems which are directly linked to the system, which is a GUID or name.
+    pub fn neighbors(&self, id: &str) -> Vec<&System> {
+        let Some(s) = self.resolve(id) else {
+            return vec![];
+        };
+
+        let mut seen = HashSet::new();
+        self.adjacency
+            .get(&normalize_guid(&s.guid))
+            .into_iter()
+            .flatten()
+            .filter(|(n, _)| seen.insert(n.clone()))
+            .filter_map(|(n, _)| self.systems.get(n))
+            .collect()
+    }
+
+    /// Find the shortest path between the systems, which are GUIDs or names; the path
+    /// only goes through switches, as the traffic is not forwarded by the hosts.
+    pub fn path(&self, from: &str, to: &str) -> Option<Vec<&System>> {
+        let from = normalize_guid(&self.resolve(from)?.guid);
+        let to = normalize_guid(&self.resolve(to)?.guid);
+
+        let mut prev: HashMap<&str, &str> = HashMap::new();
+        let mut queue = VecDeque::from([from.as_str()]);
+        let mut visited = HashSet::from([from.as_str()]);
+        while let Some(cur) = queue.pop_front() {
+            if cur == to {
+                let mut path = vec![&self.systems[cur]];
+                let mut node = cur;
+                while let Some(p) = prev.get(node) {
+                    path.push(&self.systems[*p]);
+                    node = p;
+                }
+                path.reverse();
+                return Some(path);
+            }
+
+            let forwards = cur == from
+                || self
+                    .systems
+                    .get(cur)
+                    .is_some_and(|s| s.system_type == SystemType::Switch);
+            if !forwards {
+                continue;
+            }
+
+            for (n, _) in self.adjacency.get(cur).into_iter().flatten() {
+                if self.systems.contains_key(n) && visited.insert(n.as_str()) {
+                    prev.insert(n.as_str(), cur);
+                    queue.push_back(n.as_str());
+                }
+            }
+        }
+
+        None
+    }
+
+    /// The number of links on the shortest path between the systems.
+    pub fn hop_count(&self, from: &str, to: &str) -> Option<usize> {
+        self.path(from, to).map(|p| p.len() - 1)
+    }
+}
+
+impl Ufm {
+    pub async fn list_links(&self) -> Result<Vec<Link>, UFMError> {
+        let path = String::from("/resources/links");
+        let links: Vec<Link> = self.client.list(&path).await?;
+
+        Ok(links)
+    }
+
+    /// Build the topology of the fabric from the systems, ports and links.
+    pub async fn topology(&self) -> Result<Topology, UFMError> {
+        let systems = self.list_systems(&SystemFilter::default()).await?;
+        let ports = self.list_physical_ports(&PortFilter::default()).await?;
+        let links = self.list_links().await?;
+
+        Ok(Topology::new(systems, ports, links))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn system(guid: &str, name: &str, system_type: &str) -> System {
+        serde_json::from_value(serde_json::json!({
+            "system_guid": guid,
+            "system_name": name,
+            "type": system_type,
+        }))
+        .unwrap()
+    }
+
+    fn link(src: &str, dst: &str) -> Link {
+        serde_json::from_value(serde_json::json!({
+            "source_guid": src,
+            "source_port": 1,
+            "destination_guid": dst,
+            "destination_port": 1,
+        }))
+        .unwrap()
+    }
+
+    /// host01 - leaf01 - spine01 - host02 - host03, and host04 alone.
+    fn topology() -> Topology {
+        Topology::new(
+            vec![
+                system("0000000000000001", "host01", "computer"),
+                system("0000000000000002", "host02", "computer"),
+                system("0000000000000003", "host03", "computer"),
+                system("0000000000000004", "host04", "computer"),
+                system("000000000000000a", "leaf01", "switch"),
+                system("000000000000000b", "spine01", "switch"),
+            ],
+            vec![],
+            vec![
+                link("0000000000000001", "000000000000000a"),
+                link("000000000000000a", "000000000000000b"),
+                link("000000000000000b", "0000000000000002"),
+                link("0000000000000002", "0000000000000003"),
+            ],
+        )
+    }
+
+    fn racked(guid: &str, name: &str, system_type: &str, rack: &str) -> System {
+        System {
+            rack: Some(rack.to_string()),
+            ..system(guid, name, system_type)
+        }
+    }
+
+    /// host01 - leaf01 - spine01 - leaf02 - host02, with a rack per leaf and the port
+    /// 0000000000000101 of host01.
+    fn fabric() -> Topology {
+        Topology::build(
+            vec![
+                racked("0000000000000001", "host01", "computer", "r1"),
+                racked("0000000000000002", "host02", "computer", "r2"),
+                racked("000000000000000a", "leaf01", "switch", "r1"),
+                racked("000000000000000b", "leaf02", "switch", "r2"),
+                racked("000000000000000c", "spine01", "switch", "r3"),
+            ],
+            HashMap::from([(
+                "0000000000000101".to_string(),
+                "0000000000000001".to_string(),
+            )]),
+            HashMap::from([
+                ("000000000000000a".to_string(), 1),
+                ("000000000000000b".to_string(), 1),
+                ("000000000000000c".to_string(), 2),
+            ]),
+            vec![
+                link("0000000000000101", "000000000000000a"),
+                link("000000000000000a", "000000000000000c"),
+                link("000000000000000b", "000000000000000c"),
+                link("0000000000000002", "000000000000000b"),
+            ],
+        )
+    }
+
+    fn system_names(topo: &Topology) -> Vec<String> {
+        let mut names: Vec<String> = topo.systems().map(|s| s.name.clone()).collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn filter_by_rack() {
+        let topo = fabric().filter(&TopologyFilter {
+            rack: Some("r1".to_string()),
+            ..TopologyFilter::default()
+        });
+        assert_eq!(system_names(&topo), vec!["host01", "leaf01"]);
+        assert_eq!(topo.links().len(), 1);
+    }
+
+    #[test]
+    fn filter_by_tier() {
+        let topo = fabric().filter(&TopologyFilter {
+            tiers: vec![1],
+            ..TopologyFilter::default()
+        });
+        assert_eq!(
+            system_names(&topo),
+            vec!["host01", "host02", "leaf01", "leaf02"]
+        );
+        assert_eq!(topo.links().len(), 2);
+
+        let topo = fabric().filter(&TopologyFilter {
+            tiers: vec![2],
+            ..TopologyFilter::default()
+        });
+        assert_eq!(system_names(&topo), vec!["spine01"]);
+        assert!(topo.links().is_empty());
+    }
+
+    #[test]
+    fn filter_by_systems() {
+        let topo = fabric().filter(&TopologyFilter {
+            systems: Some(vec!["0x0000000000000101".to_string()]),
+            ..TopologyFilter::default()
+        });
+        assert_eq!(system_names(&topo), vec!["host01", "leaf01"]);
+        assert_eq!(topo.links().len(), 1);
+
+        let topo = fabric().filter(&TopologyFilter {
+            systems: Some(vec![
+                "0000000000000001".to_string(),
+                "0000000000000002".to_string(),
+            ]),
+            tiers: vec![1],
+            ..TopologyFilter::default()
+        });
+        assert_eq!(
+            system_names(&topo),
+            vec!["host01", "host02", "leaf01", "leaf02"]
+        );
+    }
+
+    fn names(path: Vec<&System>) -> Vec<String> {
+        path.into_iter().map(|s| s.name.clone()).collect()
+    }
+
+    #[test]
+    fn path_between_hosts() {
+        let topo = topology();
+        let path = topo.path("host01", "0x0000000000000002").unwrap();
+        assert_eq!(names(path), vec!["host01", "leaf01", "spine01", "host02"]);
+        assert_eq!(topo.hop_count("host01", "host02"), Some(3));
+        assert_eq!(topo.hop_count("host02", "host01"), Some(3));
+    }
+
+    #[test]
+    fn path_to_itself() {
+        let topo = topology();
+        assert_eq!(
+            names(topo.path("host01", "host01").unwrap()),
+            vec!["host01"]
+        );
+        assert_eq!(topo.hop_count("host01", "host01"), Some(0));
+    }
+
+    #[test]
+    fn path_between_disconnected_systems() {
+        let topo = topology();
+        assert!(topo.path("host01", "host04").is_none());
+        assert_eq!(topo.hop_count("host01", "host04"), None);
+        assert_eq!(topo.hop_count("host01", "unknown"), None);
+    }
+
+    #[test]
+    fn path_not_through_hosts() {
+        let topo = topology();
+        assert_eq!(topo.hop_count("host02", "host03"), Some(1));
+        assert!(topo.path("host01", "host03").is_none());
+    }
+}
@@ -0,0 +1,438 @@
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+
+use serde::{Deserialize, Serialize};
+
+use crate::lookup::normalize_guid;
+use crate::{ChangeSet, Partition, PartitionKey, PartitionQoS, PortConfig, UFMError, Ufm};
+
+/// A step of the transaction.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum TransactionStep {
+    /// Add the ports to the partition, or change their membership.
+    Bind {
+        pkey: PartitionKey,
+        ipoib: bool,
+        ports: Vec<PortConfig>,
+    },
+    /// Remove the ports from the partition.
+    Unbind {
+        pkey: PartitionKey,
+        guids: Vec<String>,
+    },
+    /// Delete the partition which was created by a bind; only used by the rollback.
+    Delete { pkey: PartitionKey },
+}
+
+impl TransactionStep {
+    pub fn pkey(&self) -> PartitionKey {
+        match self {
+            TransactionStep::Bind { pkey, .. } => *pkey,
+            TransactionStep::Unbind { pkey, .. } => *pkey,
+            TransactionStep::Delete { pkey } => *pkey,
+        }
+    }
+
+    /// Build the steps which restore the membership before this step; `before` is the
+    /// ipoib and the members by normalized GUID of the partition, None if it does not exist.
+    fn inverse(
+        &self,
+        before: Option<(bool, &HashMap<String, PortConfig>)>,
+    ) -> Vec<TransactionStep> {
+        let pkey = self.pkey();
+        let (ipoib, before) = match before {
+            Some(b) => b,
+            // The bind creates the partition, so the rollback deletes it.
+            None => {
+                return match self {
+                    TransactionStep::Bind { .. } => vec![TransactionStep::Delete { pkey }],
+                    _ => vec![],
+                }
+            }
+        };
+
+        let mut inverse = vec![];
+        match self {
+            TransactionStep::Bind { ports, .. } => {
+                let mut added = vec![];
+                let mut changed = vec![];
+                for p in ports {
+                    match before.get(&normalize_guid(&p.guid)) {
+                        None => added.push(p.guid.clone()),
+                        Some(c) if c.membership != p.membership || c.index0 != p.index0 => {
+                            changed.push(c.clone())
+                        }
+                        Some(_) => {}
+                    }
+                }
+                if !added.is_empty() {
+                    inverse.push(TransactionStep::Unbind { pkey, guids: added });
+                }
+                if !changed.is_empty() {
+                    inverse.push(TransactionStep::Bind {
+                        pkey,
+                        ipoib,
+                        ports: changed,
+                    });
+                }
+            }
+            TransactionStep::Unbind { guids, .. } => {
+                let removed: Vec<PortConfig> = guids
+                    .iter()
+                    .filter_map(|g| before.get(&normalize_guid(g)).cloned())
+                    .collect();
+                if !removed.is_empty() {
+                    inverse.push(TransactionStep::Bind {
+                        pkey,
+                        ipoib,
+                        ports: removed,
+                    });
+                }
+            }
+            TransactionStep::Delete { .. } => {}
+        }
+
+        inverse
+    }
+}
+
+impl Display for TransactionStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransactionStep::Bind { pkey, ports, .. } => {
+                let ports: Vec<String> = ports
+                    .iter()
+                    .map(|p| format!("{}({})", p.guid, p.membership))
+                    .collect();
+                write!(f, "bind {}: {}", pkey, ports.join(", "))
+            }
+            TransactionStep::Unbind { pkey, guids } => {
+                write!(f, "unbind {}: {}", pkey, guids.join(", "))
+            }
+            TransactionStep::Delete { pkey } => write!(f, "delete {}", pkey),
+        }
+    }
+}
+
+/// The step which was applied, with the changes it made.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AppliedStep {
+    pub step: TransactionStep,
+    pub changes: ChangeSet,
+}
+
+/// The step which failed, with the error.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FailedStep {
+    pub step: TransactionStep,
+    pub error: String,
+}
+
+/// The report of a transaction.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct TransactionReport {
+    /// The steps which were applied, in order.
+    pub applied: Vec<AppliedStep>,
+    /// The step which failed and triggered the rollback.
+    pub failed: Option<FailedStep>,
+    /// The inverse steps which were applied by the rollback, in order.
+    pub reverted: Vec<TransactionStep>,
+    /// The inverse steps which failed in the rollback; the ports need to be fixed by hand.
+    pub not_reverted: Vec<FailedStep>,
+}
+
+impl TransactionReport {
+    pub fn is_committed(&self) -> bool {
+        self.failed.is_none()
+    }
+}
+
+/// The builder of multi-step partition operations; if a step fails, the applied
+/// steps are rolled back in reverse order with the membership captured before them.
+/// The rollback only removes the GUIDs added, or deletes the partitions created, by
+/// the transaction itself, so it is forced through the protection of the default pkey;
+/// an unbind step from a protected pkey fails before changing anything.
+pub struct Transaction<'a> {
+    ufm: &'a Ufm,
+    steps: Vec<TransactionStep>,
+}
+
+impl<'a> Transaction<'a> {
+    pub fn bind(mut self, p: &Partition, ports: Vec<PortConfig>) -> Self {
+        self.steps.push(TransactionStep::Bind {
+            pkey: p.pkey,
+            ipoib: p.ipoib,
+            ports,
+        });
+        self
+    }
+
+    pub fn unbind(mut self, pkey: PartitionKey, guids: Vec<String>) -> Self {
+        self.steps.push(TransactionStep::Unbind { pkey, guids });
+        self
+    }
+
+    /// Move the ports from one partition to another.
+    pub fn move_ports(self, from: PartitionKey, to: &Partition, ports: Vec<PortConfig>) -> Self {
+        let guids = ports.iter().map(|p| p.guid.clone()).collect();
+        self.unbind(from, guids).bind(to, ports)
+    }
+
+    /// Apply the steps in order, and roll back the applied steps on failure.
+    pub async fn commit(self) -> TransactionReport {
+        let mut report = TransactionReport::default();
+        let mut inverses: Vec<Vec<TransactionStep>> = vec![];
+
+        for step in self.steps {
+            // Capture the inverse before the step, so a partially applied step is also reverted.
+            let inverse = match self.ufm.inverse_of(&step).await {
+                Ok(inv) => inv,
+                Err(e) => {
+                    report.failed = Some(FailedStep {
+                        step,
+                        error: e.to_string(),
+                    });
+                    break;
+                }
+            };
+            inverses.push(inverse);
+
+            match self.ufm.apply_step(&step, false).await {
+                Ok(changes) => report.applied.push(AppliedStep { step, changes }),
+                Err(e) => {
+                    report.failed = Some(FailedStep {
+                        step,
+                        error: e.to_string(),
+                    });
+                    break;
+                }
+            }
+        }
+
+        if report.failed.is_none() {
+            return report;
+        }
+
+        for step in inverses.into_iter().rev().flatten() {
+            log::info!("rollback: {}", step);
+            match self.ufm.revert_step(&step).await {
+                Ok(_) => report.reverted.push(step),
+                Err(e) => report.not_reverted.push(FailedStep {
+                    step,
+                    error: e.to_string(),
+                }),
+            }
+        }
+
+        report
+    }
+}
+
+impl Ufm {
+    pub fn transaction(&self) -> Transaction<'_> {
+        Transaction {
+            ufm: self,
+            steps: vec![],
+        }
+    }
+
+    async fn apply_step(&self, step: &TransactionStep, force: bool) -> Result<ChangeSet, UFMError> {
+        match step {
+            TransactionStep::Bind { pkey, ipoib, ports } => {
+                let p = Partition {
+                    name: "".to_string(),
+                    pkey: *pkey,
+                    ipoib: *ipoib,
+                    qos: PartitionQoS::default(),
+                };
+                self.bind_ports(p, ports.clone()).await
+            }
+            TransactionStep::Unbind { pkey, guids } => {
+                self.unbind_ports(*pkey, guids.clone(), force).await
+            }
+            TransactionStep::Delete { pkey } => {
+                self.delete_partition(&pkey.to_string(), force).await?;
+                Ok(ChangeSet::default())
+            }
+        }
+    }
+
+    /// Apply the inverse step of the rollback; the partition of a failed bind may not
+    /// have been created, so there is nothing to delete then.
+    async fn revert_step(&self, step: &TransactionStep) -> Result<ChangeSet, UFMError> {
+        if let TransactionStep::Delete { pkey } = step {
+            match self.get_partition(&pkey.to_string()).await {
+                Err(UFMError::NotFound(_)) => return Ok(ChangeSet::default()),
+                Err(e) => return Err(e),
+                Ok(_) => {}
+            }
+        }
+
+        self.apply_step(step, true).await
+    }
+
+    /// Build the steps which restore the current membership of the ports in the step.
+    async fn inverse_of(&self, step: &TransactionStep) -> Result<Vec<TransactionStep>, UFMError> {
+        let pkey = step.pkey();
+        match self.get_partition(&pkey.to_string()).await {
+            Ok(p) => {
+                let members: HashMap<String, PortConfig> = self
+                    .list_partition_members(pkey)
+                    .await?
+                    .into_iter()
+                    .map(|m| (normalize_guid(&m.guid), m))
+                    .collect();
+                Ok(step.inverse(Some((p.ipoib, &members))))
+            }
+            Err(UFMError::NotFound(_)) => Ok(step.inverse(None)),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PortMembership, UFMConfig};
+
+    fn pkey(k: &str) -> PartitionKey {
+        PartitionKey::try_from(k).unwrap()
+    }
+
+    fn port(guid: &str, membership: PortMembership, index0: bool) -> PortConfig {
+        PortConfig {
+            guid: guid.to_string(),
+            membership,
+            index0,
+        }
+    }
+
+    fn members(ports: &[PortConfig]) -> HashMap<String, PortConfig> {
+        ports
+            .iter()
+            .map(|p| (normalize_guid(&p.guid), p.clone()))
+            .collect()
+    }
+
+    fn bind(ports: Vec<PortConfig>) -> TransactionStep {
+        TransactionStep::Bind {
+            pkey: pkey("0x10"),
+            ipoib: false,
+            ports,
+        }
+    }
+
+    #[test]
+    fn inverse_of_bind_to_missing_partition() {
+        let step = bind(vec![port("a", PortMembership::Full, true)]);
+
+        match step.inverse(None).as_slice() {
+            [TransactionStep::Delete { pkey: k }] => assert_eq!(*k, pkey("0x10")),
+            s => panic!("unexpected inverse {:?}", s),
+        }
+    }
+
+    #[test]
+    fn inverse_of_bind() {
+        let before = members(&[
+            port("b", PortMembership::Full, true),
+            port("c", PortMembership::Limited, true),
+        ]);
+        let step = bind(vec![
+            port("a", PortMembership::Full, true),
+            port("b", PortMembership::Limited, true),
+            port("c", PortMembership::Limited, true),
+        ]);
+
+        match step.inverse(Some((true, &before))).as_slice() {
+            [TransactionStep::Unbind { guids, .. }, TransactionStep::Bind { ipoib, ports, .. }] => {
+                assert_eq!(guids, &vec!["a".to_string()]);
+                assert!(*ipoib);
+                assert_eq!(ports, &vec![port("b", PortMembership::Full, true)]);
+            }
+            s => panic!("unexpected inverse {:?}", s),
+        }
+    }
+
+    #[test]
+    fn inverse_of_unchanged_bind() {
+        let before = members(&[port("0002C90300A1B2C3", PortMembership::Full, true)]);
+        let step = bind(vec![port("0x0002c90300a1b2c3", PortMembership::Full, true)]);
+
+        assert!(step.inverse(Some((false, &before))).is_empty());
+    }
+
+    #[test]
+    fn inverse_of_unbind() {
+        let before = members(&[
+            port("a", PortMembership::Limited, false),
+            port("b", PortMembership::Full, true),
+        ]);
+        let step = TransactionStep::Unbind {
+            pkey: pkey("0x10"),
+            guids: vec!["a".to_string(), "x".to_string()],
+        };
+
+        match step.inverse(Some((true, &before))).as_slice() {
+            [TransactionStep::Bind { ipoib, ports, .. }] => {
+                assert!(*ipoib);
+                assert_eq!(ports, &vec![port("a", PortMembership::Limited, false)]);
+            }
+            s => panic!("unexpected inverse {:?}", s),
+        }
+        assert!(step.inverse(None).is_empty());
+    }
+
+    #[tokio::test]
+    async fn rollback_bind_to_uncreated_partition() {
+        let mock = crate::mock::MockUfm::start().await;
+        mock.on("POST", "/resources/pkeys", 500, "failed");
+        let ufm = mock.ufm();
+        let p = Partition {
+            name: "".to_string(),
+            pkey: pkey("0x10"),
+            ipoib: false,
+            qos: PartitionQoS::default(),
+        };
+
+        let report = ufm
+            .transaction()
+            .bind(&p, vec![port("a", PortMembership::Full, true)])
+            .commit()
+            .await;
+
+        assert!(report.failed.is_some());
+        assert_eq!(report.reverted.len(), 1);
+        assert!(report.not_reverted.is_empty());
+        assert!(mock.changes().iter().all(|r| r.method != "DELETE"));
+    }
+
+    #[test]
+    fn move_ports_unbinds_then_binds() {
+        let ufm = crate::connect(UFMConfig {
+            address: "https://127.0.0.1".to_string(),
+            username: None,
+            password: None,
+            token: Some("token".to_string()),
+            dry_run: true,
+        })
+        .unwrap();
+        let to = Partition {
+            name: "to".to_string(),
+            pkey: pkey("0x20"),
+            ipoib: true,
+            qos: PartitionQoS::default(),
+        };
+        let tx = ufm.transaction().move_ports(
+            pkey("0x10"),
+            &to,
+            vec![port("a", PortMembership::Full, true)],
+        );
+
+        assert_eq!(
+            tx.steps.iter().map(|s| s.to_string()).collect::<Vec<_>>(),
+            vec!["unbind 0x10: a", "bind 0x20: a(full)"]
+        );
+    }
+}
@@ -0,0 +1,784 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::{self, Display};
+
+use serde::{Deserialize, Serialize};
+
+use crate::lookup::normalize_guid;
+use crate::{Link, System, SystemType, Topology, UFMError, Ufm};
+
+/// The expected design of a fat-tree fabric, e.g.
+///
+/// ```yaml
+/// tiers: 2
+/// radix: 40
+/// uplinks_per_leaf: 20
+/// speed: HDR
+/// width: 4x
+/// tier_prefixes:
+///   leaf-: 1
+///   spine-: 2
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FabricDesign {
+    /// The number of switch tiers; the leaf switches are tier 1.
+    pub tiers: u32,
+    /// The number of ports of each switch.
+    pub radix: u32,
+    pub uplinks_per_leaf: u32,
+    /// The expected speed of the links, e.g. "HDR"; the most common speed if not set.
+    #[serde(default)]
+    pub speed: Option<String>,
+    /// The expected width of the links, e.g. "4x"; the most common width if not set.
+    #[serde(default)]
+    pub width: Option<String>,
+    /// The tiers of the switches by the prefix of their names, e.g. "leaf-": 1; the
+    /// tiers are taken from UFM or inferred from the links for the other switches.
+    #[serde(default)]
+    pub tier_prefixes: BTreeMap<String, u32>,
+}
+
+/// The deviation of the fabric from its design.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ValidationIssue {
+    /// The number of switch tiers differs from the design.
+    TierMismatch { expected: u32, actual: u32 },
+    /// The switches have fewer links between them than the design.
+    MissingLink {
+        switch: String,
+        peer: String,
+        expected: u32,
+        actual: u32,
+    },
+    /// The switches have more links between them than the design.
+    ExtraLink {
+        switch: String,
+        peer: String,
+        expected: u32,
+        actual: u32,
+    },
+    /// The link is between the wrong tiers, e.g. a host linked to a spine switch.
+    CrossedLink {
+        source: String,
+        source_port: i32,
+        destination: String,
+        destination_port: i32,
+        reason: String,
+    },
+    /// The speed or width of the link differs from the design.
+    DegradedLink {
+        source: String,
+        source_port: i32,
+        destination: String,
+        destination_port: i32,
+        expected: String,
+        actual: String,
+    },
+    /// The switch has a different number of uplinks than the others in its tier.
+    AsymmetricUplinks {
+        switch: String,
+        tier: u32,
+        expected: u32,
+        actual: u32,
+    },
+    /// The switch has more links than its ports.
+    RadixExceeded {
+        switch: String,
+        radix: u32,
+        actual: u32,
+    },
+}
+
+impl Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationIssue::TierMismatch { expected, actual } => {
+                write!(f, "expected {} tiers, found {}", expected, actual)
+            }
+            ValidationIssue::MissingLink {
+                switch,
+                peer,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "missing link: {} -- {} has {} links, expected {}",
+                switch, peer, actual, expected
+            ),
+            ValidationIssue::ExtraLink {
+                switch,
+                peer,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "extra link: {} -- {} has {} links, expected {}",
+                switch, peer, actual, expected
+            ),
+            ValidationIssue::CrossedLink {
+                source,
+                source_port,
+                destination,
+                destination_port,
+                reason,
+            } => write!(
+                f,
+                "crossed link: {}/{} -- {}/{}: {}",
+                source, source_port, destination, destination_port, reason
+            ),
+            ValidationIssue::DegradedLink {
+                source,
+                source_port,
+                destination,
+                destination_port,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "degraded link: {}/{} -- {}/{} runs at {}, expected {}",
+                source, source_port, destination, destination_port, actual, expected
+            ),
+            ValidationIssue::AsymmetricUplinks {
+                switch,
+                tier,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "asymmetric uplinks: tier {} switch {} has {} uplinks, expected {}",
+                tier, switch, actual, expected
+            ),
+            ValidationIssue::RadixExceeded {
+                switch,
+                radix,
+                actual,
+            } => write!(
+                f,
+                "radix exceeded: {} has {} links, radix is {}",
+                switch, actual, radix
+            ),
+        }
+    }
+}
+
+/// The report of the validation of a fabric against its design.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ValidationReport {
+    /// The tier of each switch by name, as used by the validation.
+    pub tiers: BTreeMap<String, u32>,
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// The most common value, e.g. the speed of the links.
+fn most_common<T: Ord + Clone>(values: impl Iterator<Item = T>) -> Option<T> {
+    let mut counts: BTreeMap<T, usize> = BTreeMap::new();
+    for v in values {
+        *counts.entry(v).or_default() += 1;
+    }
+
+    counts
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(&a.0)))
+        .map(|(v, _)| v)
+}
+
+fn is_switch(s: &System) -> bool {
+    s.system_type == SystemType::Switch
+}
+
+/// Assign the tiers of the switches; the tiers in the design are used first, then the
+/// tiers reported by UFM if all switches have one. Otherwise, the leaf switches are those
+/// linked to the hosts, and the tier of the others is their distance from the leaf
+/// switches plus one; a host linked to a spine switch misleads this, so the design should
+/// have the tier prefixes for such fabrics.
+fn switch_tiers(topo: &Topology, design: &FabricDesign) -> HashMap<String, u32> {
+    let switches: Vec<&System> = topo.systems().filter(|s| is_switch(s)).collect();
+    let by_prefix = |s: &System| {
+        design
+            .tier_prefixes
+            .iter()
+            .filter(|(p, _)| s.name.starts_with(p.as_str()))
+            .max_by_key(|(p, _)| p.len())
+            .map(|(_, t)| *t)
+    };
+
+    let mut tiers = switch_tiers_from_topology(topo, &switches);
+    for s in &switches {
+        if let Some(t) = by_prefix(s) {
+            tiers.insert(normalize_guid(&s.guid), t);
+        }
+    }
+
+    tiers
+}
+
+fn switch_tiers_from_topology(topo: &Topology, switches: &[&System]) -> HashMap<String, u32> {
+    if switches.iter().all(|s| topo.tier(s).is_some_and(|t| t > 0)) {
+        return switches
+            .iter()
+            .filter_map(|s| topo.tier(s).map(|t| (normalize_guid(&s.guid), t as u32)))
+            .collect();
+    }
+
+    let mut tiers: HashMap<String, u32> = HashMap::new();
+    let mut current: Vec<String> = switches
+        .iter()
+        .filter(|s| topo.neighbors(&s.guid).iter().any(|n| !is_switch(n)))
+        .map(|s| normalize_guid(&s.guid))
+        .collect();
+    let mut tier = 1;
+    while !current.is_empty() {
+        for s in &current {
+            tiers.insert(s.clone(), tier);
+        }
+        let mut next = BTreeSet::new();
+        for s in &current {
+            for n in topo.neighbors(s) {
+                let guid = normalize_guid(&n.guid);
+                if is_switch(n) && !tiers.contains_key(&guid) {
+                    next.insert(guid);
+                }
+            }
+        }
+        current = next.into_iter().collect();
+        tier += 1;
+    }
+
+    tiers
+}
+
+/// Validate the topology against the fat-tree design.
+pub fn validate_fat_tree(topo: &Topology, design: &FabricDesign) -> ValidationReport {
+    let tiers = switch_tiers(topo, design);
+    let mut issues = vec![];
+
+    // The switches are counted by GUID, as the names are not unique; the names are
+    // only used in the issues.
+    let name = |s: &System| s.name.clone();
+    let guid = |s: &System| normalize_guid(&s.guid);
+    let tier_of = |s: &System| tiers.get(&guid(s)).copied();
+    let names: HashMap<String, String> = topo.systems().map(|s| (guid(s), name(s))).collect();
+
+    let actual_tiers = tiers.values().copied().max().unwrap_or_default();
+    if actual_tiers != design.tiers {
+        issues.push(ValidationIssue::TierMismatch {
+            expected: design.tiers,
+            actual: actual_tiers,
+        });
+    }
+
+    let links: Vec<(&Link, &System, &System)> = topo
+        .links()
+        .iter()
+        .filter_map(|l| match topo.endpoints(l) {
+            (Some(src), Some(dst)) => Some((l, src, dst)),
+            _ => None,
+        })
+        .collect();
+
+    let speed = design
+        .speed
+        .clone()
+        .or_else(|| most_common(links.iter().filter_map(|(l, _, _)| l.speed.clone())));
+    let width = design
+        .width
+        .clone()
+        .or_else(|| most_common(links.iter().filter_map(|(l, _, _)| l.width.clone())));
+
+    let mut used: BTreeMap<String, u32> = BTreeMap::new();
+    let mut uplinks: BTreeMap<String, u32> = BTreeMap::new();
+    let mut pairs: BTreeMap<(String, String), u32> = BTreeMap::new();
+    for (link, src, dst) in &links {
+        *used.entry(guid(src)).or_default() += 1;
+        *used.entry(guid(dst)).or_default() += 1;
+
+        let crossed = |reason: String| ValidationIssue::CrossedLink {
+            source: name(src),
+            source_port: link.source_port_number,
+            destination: name(dst),
+            destination_port: link.destination_port_number,
+            reason,
+        };
+        match (tier_of(src), tier_of(dst)) {
+            (None, None) => issues.push(crossed("host linked to host".to_string())),
+            (Some(t), None) | (None, Some(t)) if t != 1 => {
+                issues.push(crossed(format!("host linked to tier {} switch", t)))
+            }
+            (Some(a), Some(b)) if a.abs_diff(b) != 1 => {
+                issues.push(crossed(format!("tier {} switch linked to tier {}", a, b)))
+            }
+            (Some(a), Some(b)) => {
+                let (lower, upper) = if a < b { (src, dst) } else { (dst, src) };
+                *uplinks.entry(guid(lower)).or_default() += 1;
+                *pairs.entry((guid(lower), guid(upper))).or_default() += 1;
+            }
+            _ => {}
+        }
+
+        let expected = |v: &Option<String>, e: &Option<String>| match (v, e) {
+            (Some(v), Some(e)) => v.eq_ignore_ascii_case(e),
+            _ => true,
+        };
+        if !expected(&link.speed, &speed) || !expected(&link.width, &width) {
+            let join = |s: &Option<String>, w: &Option<String>| {
+                format!(
+                    "{} {}",
+                    s.as_deref().unwrap_or("?"),
+                    w.as_deref().unwrap_or("?")
+                )
+            };
+            issues.push(ValidationIssue::DegradedLink {
+                source: name(src),
+                source_port: link.source_port_number,
+                destination: name(dst),
+                destination_port: link.destination_port_number,
+                expected: join(&speed, &width),
+                actual: join(&link.speed, &link.width),
+            });
+        }
+    }
+
+    let mut switches: Vec<&System> = topo.systems().filter(|s| is_switch(s)).collect();
+    switches.sort_by_key(|s| (name(s), guid(s)));
+
+    for s in &switches {
+        let actual = used.get(&guid(s)).copied().unwrap_or_default();
+        if actual > design.radix {
+            issues.push(ValidationIssue::RadixExceeded {
+                switch: name(s),
+                radix: design.radix,
+                actual,
+            });
+        }
+    }
+
+    // The leaf switches are checked against the design, and the switches of the other
+    // tiers below the top one against the most common number of uplinks in their tier.
+    for tier in 1..actual_tiers {
+        let members: Vec<&&System> = switches
+            .iter()
+            .filter(|s| tier_of(s) == Some(tier))
+            .collect();
+        let count = |s: &System| uplinks.get(&guid(s)).copied().unwrap_or_default();
+        let expected = match tier {
+            1 => design.uplinks_per_leaf,
+            _ => most_common(members.iter().map(|s| count(s))).unwrap_or_default(),
+        };
+        for s in members {
+            if count(s) != expected {
+                issues.push(ValidationIssue::AsymmetricUplinks {
+                    switch: name(s),
+                    tier,
+                    expected,
+                    actual: count(s),
+                });
+            }
+        }
+    }
+
+    // The leaf and tier 2 switches of a pod are fully meshed, with the uplinks of the
+    // leaf switches spread evenly over the tier 2 switches.
+    for (leaves, spines) in pods(&switches, &tiers, &pairs) {
+        if spines.is_empty() {
+            continue;
+        }
+        let n = spines.len() as u32;
+        let (min, max) = (
+            design.uplinks_per_leaf / n,
+            design.uplinks_per_leaf.div_ceil(n),
+        );
+        for leaf in &leaves {
+            for spine in &spines {
+                let actual = pairs
+                    .get(&(leaf.clone(), spine.clone()))
+                    .copied()
+                    .unwrap_or_default();
+                if actual < min {
+                    issues.push(ValidationIssue::MissingLink {
+                        switch: names[leaf].clone(),
+                        peer: names[spine].clone(),
+                        expected: min,
+                        actual,
+                    });
+                } else if actual > max {
+                    issues.push(ValidationIssue::ExtraLink {
+                        switch: names[leaf].clone(),
+                        peer: names[spine].clone(),
+                        expected: max,
+                        actual,
+                    });
+                }
+            }
+        }
+    }
+
+    ValidationReport {
+        tiers: switches
+            .iter()
+            .filter_map(|s| tier_of(s).map(|t| (name(s), t)))
+            .collect(),
+        issues,
+    }
+}
+
+/// Group the leaf and tier 2 switches by the pods they are linked in; the switches are
+/// GUIDs, in the order of the given switches.
+fn pods(
+    switches: &[&System],
+    tiers: &HashMap<String, u32>,
+    pairs: &BTreeMap<(String, String), u32>,
+) -> Vec<(Vec<String>, Vec<String>)> {
+    let mut pod_of: HashMap<String, usize> = HashMap::new();
+    let mut pods: Vec<(Vec<String>, Vec<String>)> = vec![];
+
+    let tier_of = |g: &str| tiers.get(g).copied();
+    let order: HashMap<String, usize> = switches
+        .iter()
+        .enumerate()
+        .map(|(i, s)| (normalize_guid(&s.guid), i))
+        .collect();
+    let links: Vec<(&String, &String)> = pairs
+        .keys()
+        .filter(|(l, u)| tier_of(l) == Some(1) && tier_of(u) == Some(2))
+        .map(|(l, u)| (l, u))
+        .collect();
+
+    for s in switches.iter().map(|s| normalize_guid(&s.guid)) {
+        if tier_of(&s) != Some(1) || pod_of.contains_key(&s) {
+            continue;
+        }
+
+        // Walk the leaf and tier 2 switches which are linked to each other.
+        let id = pods.len();
+        let mut pod = (vec![], vec![]);
+        let mut stack = vec![(s, true)];
+        while let Some((n, is_leaf)) = stack.pop() {
+            if pod_of.insert(n.clone(), id).is_some() {
+                continue;
+            }
+            for (l, u) in &links {
+                if is_leaf && **l == n {
+                    stack.push(((*u).clone(), false));
+                } else if !is_leaf && **u == n {
+                    stack.push(((*l).clone(), true));
+                }
+            }
+            match is_leaf {
+                true => pod.0.push(n),
+                false => pod.1.push(n),
+            };
+        }
+        pod.0.sort_by_key(|g| order.get(g).copied());
+        pod.1.sort_by_key(|g| order.get(g).copied());
+        pods.push(pod);
+    }
+
+    pods
+}
+
+impl Ufm {
+    /// Validate the topology of the fabric against the fat-tree design.
+    pub async fn validate_fat_tree(
+        &self,
+        design: &FabricDesign,
+    ) -> Result<ValidationReport, UFMError> {
+        Ok(validate_fat_tree(&self.topology().await?, design))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The systems are named "<kind>-<n>", e.g. "leaf-1", and the hosts "host-<n>".
+    struct Fabric {
+        systems: Vec<System>,
+        links: Vec<Link>,
+    }
+
+    impl Fabric {
+        fn new() -> Self {
+            Fabric {
+                systems: vec![],
+                links: vec![],
+            }
+        }
+
+        fn system(&mut self, name: &str, system_type: &str) -> String {
+            let guid = format!("{:016x}", self.systems.len() + 1);
+            self.systems.push(
+                serde_json::from_value(serde_json::json!({
+                    "system_guid": guid,
+                    "system_name": name,
+                    "type": system_type,
+                }))
+                .unwrap(),
+            );
+            guid
+        }
+
+        fn switch(&mut self, name: &str) -> String {
+            self.system(name, "switch")
+        }
+
+        fn host(&mut self, name: &str) -> String {
+            self.system(name, "computer")
+        }
+
+        fn link_at(&mut self, src: &str, dst: &str, speed: &str) {
+            let port = self.links.len() as i32 + 1;
+            self.links.push(
+                serde_json::from_value(serde_json::json!({
+                    "source_guid": src,
+                    "source_port": port,
+                    "destination_guid": dst,
+                    "destination_port": port,
+                    "speed": speed,
+                    "width": "4x",
+                }))
+                .unwrap(),
+            );
+        }
+
+        fn link(&mut self, src: &str, dst: &str) {
+            self.link_at(src, dst, "HDR");
+        }
+
+        fn unlink(&mut self, src: &str, dst: &str) {
+            let i = self
+                .links
+                .iter()
+                .position(|l| l.source_guid == src && l.destination_guid == dst)
+                .unwrap();
+            self.links.remove(i);
+        }
+
+        fn topology(&self) -> Topology {
+            Topology::new(self.systems.clone(), vec![], self.links.clone())
+        }
+    }
+
+    fn design(tiers: u32, uplinks_per_leaf: u32) -> FabricDesign {
+        FabricDesign {
+            tiers,
+            radix: 8,
+            uplinks_per_leaf,
+            speed: Some("HDR".to_string()),
+            width: Some("4x".to_string()),
+            tier_prefixes: BTreeMap::new(),
+        }
+    }
+
+    /// Two leaf switches with a host each, linked to two spine switches.
+    fn two_tier() -> (Fabric, Vec<String>, Vec<String>, Vec<String>) {
+        let mut f = Fabric::new();
+        let spines: Vec<String> = (1..=2).map(|i| f.switch(&format!("spine-{}", i))).collect();
+        let leaves: Vec<String> = (1..=2).map(|i| f.switch(&format!("leaf-{}", i))).collect();
+        let hosts: Vec<String> = (1..=2).map(|i| f.host(&format!("host-{}", i))).collect();
+        for (leaf, host) in leaves.iter().zip(&hosts) {
+            f.link(host, leaf);
+            for spine in &spines {
+                f.link(leaf, spine);
+            }
+        }
+
+        (f, spines, leaves, hosts)
+    }
+
+    /// Two pods of two leaf and two tier 2 switches, linked to two core switches.
+    fn three_tier() -> (Fabric, Vec<String>, Vec<String>) {
+        let mut f = Fabric::new();
+        let cores: Vec<String> = (1..=2).map(|i| f.switch(&format!("core-{}", i))).collect();
+        let mut aggs = vec![];
+        for pod in ["a", "b"] {
+            let pod_aggs: Vec<String> = (1..=2)
+                .map(|i| f.switch(&format!("agg-{}{}", pod, i)))
+                .collect();
+            for i in 1..=2 {
+                let leaf = f.switch(&format!("leaf-{}{}", pod, i));
+                let host = f.host(&format!("host-{}{}", pod, i));
+                f.link(&host, &leaf);
+                for agg in &pod_aggs {
+                    f.link(&leaf, agg);
+                }
+            }
+            for agg in &pod_aggs {
+                for core in &cores {
+                    f.link(agg, core);
+                }
+            }
+            aggs.extend(pod_aggs);
+        }
+
+        (f, cores, aggs)
+    }
+
+    #[test]
+    fn valid_two_tier() {
+        let (f, ..) = two_tier();
+        let report = validate_fat_tree(&f.topology(), &design(2, 2));
+        assert!(report.is_valid(), "{:?}", report.issues);
+        assert_eq!(report.tiers["leaf-1"], 1);
+        assert_eq!(report.tiers["spine-2"], 2);
+    }
+
+    #[test]
+    fn valid_three_tier() {
+        let (f, ..) = three_tier();
+        let report = validate_fat_tree(&f.topology(), &design(3, 2));
+        assert!(report.is_valid(), "{:?}", report.issues);
+        assert_eq!(report.tiers["leaf-b2"], 1);
+        assert_eq!(report.tiers["agg-a1"], 2);
+        assert_eq!(report.tiers["core-1"], 3);
+    }
+
+    #[test]
+    fn tier_mismatch() {
+        let (f, ..) = two_tier();
+        let report = validate_fat_tree(&f.topology(), &design(3, 2));
+        assert!(report.issues.contains(&ValidationIssue::TierMismatch {
+            expected: 3,
+            actual: 2,
+        }));
+    }
+
+    #[test]
+    fn missing_and_extra_link() {
+        let (mut f, spines, leaves, _) = two_tier();
+        f.unlink(&leaves[0], &spines[1]);
+        f.link(&leaves[0], &spines[0]);
+
+        let report = validate_fat_tree(&f.topology(), &design(2, 2));
+        assert_eq!(
+            report.issues,
+            vec![
+                ValidationIssue::ExtraLink {
+                    switch: "leaf-1".to_string(),
+                    peer: "spine-1".to_string(),
+                    expected: 1,
+                    actual: 2,
+                },
+                ValidationIssue::MissingLink {
+                    switch: "leaf-1".to_string(),
+                    peer: "spine-2".to_string(),
+                    expected: 1,
+                    actual: 0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn crossed_link() {
+        let (mut f, spines, leaves, hosts) = two_tier();
+        f.link(&hosts[0], &spines[0]);
+        f.link(&leaves[0], &leaves[1]);
+
+        let mut design = design(2, 2);
+        design.tier_prefixes =
+            BTreeMap::from([("leaf-".to_string(), 1), ("spine-".to_string(), 2)]);
+        let report = validate_fat_tree(&f.topology(), &design);
+        let reasons: Vec<&str> = report
+            .issues
+            .iter()
+            .filter_map(|i| match i {
+                ValidationIssue::CrossedLink { reason, .. } => Some(reason.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                "host linked to tier 2 switch",
+                "tier 1 switch linked to tier 1"
+            ]
+        );
+    }
+
+    #[test]
+    fn degraded_link() {
+        let (mut f, spines, leaves, _) = two_tier();
+        f.unlink(&leaves[1], &spines[0]);
+        f.link_at(&leaves[1], &spines[0], "EDR");
+
+        let report = validate_fat_tree(&f.topology(), &design(2, 2));
+        assert_eq!(report.issues.len(), 1);
+        assert!(matches!(
+            &report.issues[0],
+            ValidationIssue::DegradedLink { source, expected, actual, .. }
+                if source == "leaf-2" && expected == "HDR 4x" && actual == "EDR 4x"
+        ));
+    }
+
+    #[test]
+    fn asymmetric_uplinks() {
+        let (mut f, spines, leaves, _) = two_tier();
+        f.unlink(&leaves[1], &spines[1]);
+
+        let report = validate_fat_tree(&f.topology(), &design(2, 2));
+        assert!(report.issues.contains(&ValidationIssue::AsymmetricUplinks {
+            switch: "leaf-2".to_string(),
+            tier: 1,
+            expected: 2,
+            actual: 1,
+        }));
+    }
+
+    #[test]
+    fn asymmetric_uplinks_above_leaves() {
+        let (mut f, cores, aggs) = three_tier();
+        f.unlink(&aggs[3], &cores[1]);
+
+        let report = validate_fat_tree(&f.topology(), &design(3, 2));
+        assert_eq!(
+            report.issues,
+            vec![ValidationIssue::AsymmetricUplinks {
+                switch: "agg-b2".to_string(),
+                tier: 2,
+                expected: 2,
+                actual: 1,
+            }]
+        );
+    }
+
+    #[test]
+    fn radix_exceeded() {
+        let (f, ..) = two_tier();
+        let mut design = design(2, 2);
+        design.radix = 2;
+
+        let report = validate_fat_tree(&f.topology(), &design);
+        let switches: Vec<&str> = report
+            .issues
+            .iter()
+            .filter_map(|i| match i {
+                ValidationIssue::RadixExceeded { switch, actual, .. } if *actual == 3 => {
+                    Some(switch.as_str())
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(switches, vec!["leaf-1", "leaf-2"]);
+    }
+
+    #[test]
+    fn switches_with_same_name() {
+        let mut f = Fabric::new();
+        let spine = f.switch("spine");
+        for i in 1..=2 {
+            let leaf = f.switch("leaf");
+            let host = f.host(&format!("host-{}", i));
+            f.link(&host, &leaf);
+            f.link(&leaf, &spine);
+        }
+
+        let report = validate_fat_tree(&f.topology(), &design(2, 1));
+        assert!(report.is_valid(), "{:?}", report.issues);
+    }
+}